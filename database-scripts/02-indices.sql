@@ -1 +0,0 @@
-CREATE INDEX idx_blogpost_dateofpublication ON BlogPost (dateOfPublication);
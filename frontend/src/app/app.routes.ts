@@ -1,8 +1,11 @@
 import { Routes } from '@angular/router';
 import { HomeComponent } from './home/home.component';
+import { PostComponent } from './post/post.component';
 
 export const routes: Routes = [
   { path: 'home', component: HomeComponent},
+  { path: 'post/by-slug/:slug', component: PostComponent},
+  { path: 'post/:id', component: PostComponent},
   { path: '', redirectTo: 'home', pathMatch: 'full'},
   { path: '**', redirectTo: 'home'},
 ];
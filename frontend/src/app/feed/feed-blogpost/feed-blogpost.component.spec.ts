@@ -1,4 +1,5 @@
 import { ComponentFixture, TestBed } from '@angular/core/testing';
+import { provideRouter } from '@angular/router';
 
 import { FeedBlogpostComponent } from './feed-blogpost.component';
 
@@ -8,7 +9,8 @@ describe('FeedBlogpostComponent', () => {
 
   beforeEach(async () => {
     await TestBed.configureTestingModule({
-      imports: [FeedBlogpostComponent]
+      imports: [FeedBlogpostComponent],
+      providers: [provideRouter([])]
     })
     .compileComponents();
     
@@ -1,12 +1,13 @@
 import { CommonModule } from '@angular/common';
 import { HttpClientModule } from '@angular/common/http';
 import { Component, ElementRef, Input, ViewChild } from '@angular/core';
+import { RouterLink } from '@angular/router';
 import { ImageService } from '../../services/image.service';
 
 @Component({
   selector: 'app-feed-blogpost',
   standalone: true,
-  imports: [HttpClientModule, CommonModule],
+  imports: [HttpClientModule, CommonModule, RouterLink],
   providers: [ImageService],
   templateUrl: './feed-blogpost.component.html',
   styleUrl: './feed-blogpost.component.scss'
@@ -16,14 +17,26 @@ export class FeedBlogpostComponent {
     this.imageService = imageService;
   }
   @ViewChild('blogpostText') blogPostText!: ElementRef
-  @Input() text: String = ''
+  @Input() id: Number = 0
+  @Input() slug: String | null = null
+  @Input() title: String | null = null
+  @Input() excerpt: String = ''
   @Input() username: String = ''
-  @Input() dateOfPublication: String = ''
+  @Input() publishedAt: string = ''
   @Input() avatarId: String | null = null
   @Input() postImageId: String | null = null
+  @Input() commentCount: number = 0
+  @Input() reactions: { [kind: string]: number } = {}
+  @Input() tags: String[] = []
   public avatarImage: String | null = null;
   public postImage: String | null = null;
 
+  // posts with a title have a slug and are linked by it
+  get link(): String[] {
+    if (this.slug != null) return ['/post/by-slug', this.slug];
+    return ['/post', this.id.toString()];
+  }
+
   ngOnInit(): void {
     this.getAvatar()
     this.getPostImage()
@@ -38,7 +51,7 @@ export class FeedBlogpostComponent {
   getAvatar() {
     let id = this.avatarId;
     if (id == null) id = 'placeholder_avatar';
-    let resp = this.imageService.get(id);
+    let resp = this.imageService.get(id, 'avatar');
     resp.subscribe(
       avatar => {
         const objectUrl = URL.createObjectURL(avatar);
@@ -52,7 +65,7 @@ export class FeedBlogpostComponent {
 
   getPostImage() {
     if (this.postImageId == null) return;
-    let resp = this.imageService.get(this.postImageId);
+    let resp = this.imageService.get(this.postImageId, 'large');
     resp.subscribe(
       postImage => {
         const objectUrl = URL.createObjectURL(postImage);
@@ -1,7 +1,8 @@
 import { HttpClientModule } from '@angular/common/http';
 import { Component } from '@angular/core';
 import { BlogpostService } from '../services/blogpost.service';
-import { Blogpost } from '../models/blogpost.model';
+import { FeedItem } from '../models/blogpost.model';
+import { FeedDTO } from '../models/feed-dto.model';
 import { FeedBlogpostComponent } from './feed-blogpost/feed-blogpost.component';
 import { CommonModule } from '@angular/common';
 
@@ -18,8 +19,10 @@ export class FeedComponent {
     this.blogpostService = blogpostService;
   }
 
-  public blogposts: Blogpost[] = [];
+  public blogposts: FeedItem[] = [];
   public page: number = 1;
+  public hasNext: boolean = false;
+  public hasPrevious: boolean = false;
 
   ngOnInit(): void {
     this.fetchFeed();
@@ -28,9 +31,10 @@ export class FeedComponent {
   fetchFeed() {
     let resp = this.blogpostService.getFeed(this.page);
     resp.subscribe(
-      (data: string) => {
-        let dataParsed = JSON.parse(data);
-        this.blogposts = dataParsed.blogposts;
+      (data: FeedDTO) => {
+        this.blogposts = data.blogposts;
+        this.hasNext = data.has_next;
+        this.hasPrevious = data.has_previous;
       },
       _ => {
         alert('Error loading feed!')
@@ -39,11 +43,13 @@ export class FeedComponent {
   }
 
   previous() {
-    if (this.page > 1) this.page -= 1;
+    if (!this.hasPrevious) return;
+    this.page -= 1;
     this.fetchFeed();
   }
 
   next() {
+    if (!this.hasNext) return;
     this.page += 1;
     this.fetchFeed();
   }
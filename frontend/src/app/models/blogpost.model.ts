@@ -1,8 +1,21 @@
 export interface Blogpost {
   id: Number,
+  title: String | null,
+  slug: String | null,
   text: String,
+  html: string,
+  excerpt: String,
   username: String,
-  date_of_publication: String,
+  published_at: string,
+  status: string,
+  publish_at: string | null,
   avatar: String | null,
-  post_image: String | null
+  post_image: String | null,
+  edited_at: string | null,
+  comment_count: number,
+  reactions: { [kind: string]: number },
+  tags: String[]
 }
+
+// post as it is listed in the feed, the text is fetched with the single post
+export type FeedItem = Omit<Blogpost, 'text' | 'html'>
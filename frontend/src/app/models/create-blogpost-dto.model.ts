@@ -1,6 +1,8 @@
 
 export interface CreateBlogPostDTO {
   text: String,
-  username: String,
-  avatar: String | null
+  title: String | null,
+  tags: String[],
+  status?: string,
+  publish_at?: string | null
 }
@@ -0,0 +1,4 @@
+export interface CredentialsDTO {
+  username: String,
+  password: String
+}
@@ -1,5 +1,11 @@
-import { Blogpost } from "./blogpost.model";
+import { FeedItem } from "./blogpost.model";
 
 export interface FeedDTO {
-  blogposts: Blogpost[]
+  blogposts: FeedItem[],
+  next_cursor: String | null,
+  page: number | null,
+  page_size: number,
+  total_count: number,
+  has_next: boolean,
+  has_previous: boolean
 }
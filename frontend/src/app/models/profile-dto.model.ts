@@ -0,0 +1,6 @@
+export interface ProfileDTO {
+  username: String,
+  display_name: String | null,
+  bio: String | null,
+  avatar: String | null
+}
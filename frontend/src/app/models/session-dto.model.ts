@@ -0,0 +1,4 @@
+export interface SessionDTO {
+  token: string,
+  expires_at: string
+}
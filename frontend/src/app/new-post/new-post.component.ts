@@ -4,12 +4,14 @@ import { FormsModule } from '@angular/forms';
 import { CreateBlogPostDTO } from '../models/create-blogpost-dto.model';
 import { BlogpostService } from '../services/blogpost.service';
 import { HttpClientModule } from '@angular/common/http';
+import { UserService } from '../services/user.service';
+import { CredentialsDTO } from '../models/credentials-dto.model';
 
 @Component({
   selector: 'app-new-post',
   standalone: true,
   imports: [FormsModule, CommonModule, HttpClientModule],
-  providers: [BlogpostService],
+  providers: [BlogpostService, UserService],
   templateUrl: './new-post.component.html',
   styleUrl: './new-post.component.scss'
 })
@@ -18,14 +20,18 @@ export class NewPostComponent {
   public showErrorMessage = false;
 
   public username = "";
+  public password = "";
   public avatarURL = "";
+  public tags = "";
+  public title = "";
   public text = "";
   public postImageFile: File | null = null;
   public postImage: Blob | null = null;
   public isPostDisabled = false;
 
-  constructor(private blogpostService: BlogpostService) {
+  constructor(private blogpostService: BlogpostService, private userService: UserService) {
     this.blogpostService = blogpostService;
+    this.userService = userService;
   }
 
   onFileSelected(files: FileList | null) {
@@ -35,6 +41,29 @@ export class NewPostComponent {
     }
   }
 
+  onRegisterAndSubmit() {
+    let credentials: CredentialsDTO = { username: this.username, password: this.password };
+    this.isPostDisabled = true;
+    this.userService.register(credentials).subscribe(
+      _ => this.onSubmit(),
+      err => this.showRequestError(err, "Unable to register, try again later!")
+    )
+  }
+
+  showRequestError(err: any, fallback: string) {
+    if (err.status == 400 || err.status == 401 || err.status == 409) {
+      try {
+        this.errorMessage = err.error['error'];
+      } catch {
+        this.errorMessage = fallback;
+      }
+    } else {
+      this.errorMessage = fallback;
+    }
+    this.showErrorMessage = true;
+    this.isPostDisabled = false;
+  }
+
   onSubmit() {
     this.errorMessage = "";
     this.showErrorMessage = false;
@@ -57,8 +86,15 @@ export class NewPostComponent {
       this.showErrorMessage = true;
       this.isPostDisabled = false;
       return;
-    } else if (this.text.length > 2000) {
-      this.errorMessage = "Text of the post cannot be longer than 2000 characters!";
+    } else if (new TextEncoder().encode(this.text).length > 100000) {
+      this.errorMessage = "Text of the post cannot be longer than 100000 bytes!";
+      this.showErrorMessage = true;
+      this.isPostDisabled = false;
+      return;
+    }
+
+    if (this.title.trim().length > 200) {
+      this.errorMessage = "Title of the post cannot be longer than 200 characters!";
       this.showErrorMessage = true;
       this.isPostDisabled = false;
       return;
@@ -71,43 +107,53 @@ export class NewPostComponent {
 
     let dto: CreateBlogPostDTO = {
       text: this.text,
-      username: this.username,
-      avatar: this.avatarURL.length != 0 ? this.avatarURL : null
+      title: this.title.trim().length > 0 ? this.title : null,
+      tags: this.tags.split(',').map(tag => tag.trim()).filter(tag => tag.length > 0)
     }
 
-    let resp = this.blogpostService.create(dto, this.postImage);
-    resp.subscribe(
+    let credentials: CredentialsDTO = { username: this.username, password: this.password };
+    this.userService.login(credentials).subscribe(
       _ => {
-        location.reload();
-      },
-      err => {
-        if (err.status == 400) {
-          try {
-            this.errorMessage = err.error['error'];
-          } catch {
-            this.errorMessage = "Unable to create a post, try again later!";
-          }
-        } else {
-          this.errorMessage = "Unable to create a post, try again later!";
+        if (this.avatarURL.length == 0) {
+          this.createPost(dto);
+          return;
         }
-        this.showErrorMessage = true;
-        this.isPostDisabled = false;
-      }
+        // the avatar is stored in the profile and shown on all posts of the user
+        this.userService.setAvatarFromUrl(this.avatarURL).subscribe(
+          _ => this.createPost(dto),
+          err => this.showRequestError(err, "Unable to set the avatar, try again later!")
+        )
+      },
+      err => this.showRequestError(err, "Unable to log in, try again later!")
     )
 
   }
 
+  createPost(dto: CreateBlogPostDTO) {
+    this.blogpostService.create(dto, this.postImage).subscribe(
+      _ => {
+        location.reload();
+      },
+      err => this.showRequestError(err, "Unable to create a post, try again later!")
+    )
+  }
+
   loadPostImage() {
     if (this.postImageFile == null) return;
     const reader = new FileReader();
 
     reader.onload = () => {
       const byteArray = new Uint8Array(reader.result as ArrayBuffer);
-      const pngSignature = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
-      const isPng = pngSignature.every((byte, index) => byteArray[index] === byte);
-
-      if (!isPng) {
-        this.errorMessage = 'Post image is not a PNG!';
+      const startsWith = (signature: number[], offset = 0) =>
+        signature.every((byte, index) => byteArray[offset + index] === byte);
+      const ascii = (text: string) => Array.from(text, char => char.charCodeAt(0));
+      const isImage = startsWith([0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A])
+        || startsWith([0xFF, 0xD8, 0xFF])
+        || startsWith(ascii('GIF87a')) || startsWith(ascii('GIF89a'))
+        || (startsWith(ascii('RIFF')) && startsWith(ascii('WEBP'), 8));
+
+      if (!isImage) {
+        this.errorMessage = 'Post image has to be a PNG, JPEG, GIF or WebP!';
         this.showErrorMessage = true;
         this.postImage = null;
       } else if (byteArray.length > 2*1024*1024)  {
@@ -0,0 +1,73 @@
+import { CommonModule } from '@angular/common';
+import { HttpClientModule } from '@angular/common/http';
+import { Component } from '@angular/core';
+import { ActivatedRoute, RouterLink } from '@angular/router';
+import { Observable } from 'rxjs';
+import { Blogpost } from '../models/blogpost.model';
+import { BlogpostService } from '../services/blogpost.service';
+import { ImageService } from '../services/image.service';
+
+@Component({
+  selector: 'app-post',
+  standalone: true,
+  imports: [HttpClientModule, CommonModule, RouterLink],
+  providers: [BlogpostService, ImageService],
+  templateUrl: './post.component.html',
+  styleUrl: './post.component.scss'
+})
+export class PostComponent {
+  constructor(private route: ActivatedRoute, private blogpostService: BlogpostService, private imageService: ImageService) {
+    this.route = route;
+    this.blogpostService = blogpostService;
+    this.imageService = imageService;
+  }
+
+  public post: Blogpost | null = null;
+  public notFound: boolean = false;
+  public avatarImage: String | null = null;
+  public postImage: String | null = null;
+
+  ngOnInit(): void {
+    // posts with a title are linked by their slug, the others by their id
+    this.route.paramMap.subscribe(params => {
+      const slug = params.get('slug');
+      const id = params.get('id');
+      let resp: Observable<Blogpost>;
+      if (slug != null) resp = this.blogpostService.getBySlug(slug);
+      else resp = this.blogpostService.getById(Number(id));
+      this.fetchPost(resp);
+    })
+  }
+
+  fetchPost(resp: Observable<Blogpost>) {
+    resp.subscribe(
+      post => {
+        this.post = post;
+        this.notFound = false;
+        this.getAvatar(post.avatar);
+        this.getPostImage(post.post_image);
+      },
+      _ => {
+        this.post = null;
+        this.notFound = true;
+      }
+    )
+  }
+
+  getAvatar(id: String | null) {
+    if (id == null) id = 'placeholder_avatar';
+    this.imageService.get(id, 'avatar').subscribe(
+      avatar => this.avatarImage = URL.createObjectURL(avatar),
+      err => console.log(err)
+    )
+  }
+
+  getPostImage(id: String | null) {
+    this.postImage = null;
+    if (id == null) return;
+    this.imageService.get(id, 'large').subscribe(
+      postImage => this.postImage = URL.createObjectURL(postImage),
+      err => console.log(err)
+    )
+  }
+}
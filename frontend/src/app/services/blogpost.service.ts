@@ -1,8 +1,11 @@
 import { Injectable } from "@angular/core";
 import { CreateBlogPostDTO } from "../models/create-blogpost-dto.model";
+import { FeedDTO } from "../models/feed-dto.model";
+import { Blogpost } from "../models/blogpost.model";
 import { Observable } from "rxjs";
 import { HttpClient } from "@angular/common/http";
 import { environment } from "../../environments/environment";
+import { UserService } from "./user.service";
 
 @Injectable({
   providedIn: 'root'
@@ -18,10 +21,19 @@ export class BlogpostService {
     formData.append('data', JSON.stringify(dto));
     if (postImage != null) { formData.append('image', postImage, 'image.png'); }
 
-    return this.http.post<null>(this.baseUrl, formData);
+    return this.http.post<null>(this.baseUrl, formData, { headers: UserService.authHeaders() });
   }
 
-  public getFeed(page: Number): Observable<string> {
-    return this.http.get<string>(this.baseUrl + `?page=${page}`);
+  public getFeed(page: Number): Observable<FeedDTO> {
+    return this.http.get<FeedDTO>(this.baseUrl + `?page=${page}`);
+  }
+
+  // drafts are only returned to their author, so the session is sent along
+  public getById(id: Number): Observable<Blogpost> {
+    return this.http.get<Blogpost>(this.baseUrl + `/${id}`, { headers: UserService.authHeaders() });
+  }
+
+  public getBySlug(slug: String): Observable<Blogpost> {
+    return this.http.get<Blogpost>(this.baseUrl + `/by-slug/${slug}`, { headers: UserService.authHeaders() });
   }
 }
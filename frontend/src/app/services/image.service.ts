@@ -12,7 +12,9 @@ export class ImageService {
 
   constructor(private http: HttpClient) {}
 
-  public get(id: String): Observable<Blob> {
-    return this.http.get<Blob>(this.baseUrl + `/${id}`, {responseType: 'blob' as 'json'});
+  // variant is one of 'avatar' (64px), 'small' (480px) or 'large' (1080px), narrower images are sent as they are
+  public get(id: String, variant: String | null = null): Observable<Blob> {
+    const path = variant == null ? `/${id}` : `/${id}/${variant}`;
+    return this.http.get<Blob>(this.baseUrl + path, {responseType: 'blob' as 'json'});
   }
 }
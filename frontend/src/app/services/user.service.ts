@@ -0,0 +1,40 @@
+import { Injectable } from "@angular/core";
+import { Observable, tap } from "rxjs";
+import { HttpClient, HttpHeaders } from "@angular/common/http";
+import { environment } from "../../environments/environment";
+import { CredentialsDTO } from "../models/credentials-dto.model";
+import { SessionDTO } from "../models/session-dto.model";
+import { ProfileDTO } from "../models/profile-dto.model";
+
+const TOKEN_KEY = 'token';
+
+@Injectable({
+  providedIn: 'root'
+})
+export class UserService {
+  private baseUrl: string = `${environment.serverUrl}/api/v1/users`
+
+  constructor(private http: HttpClient) {}
+
+  public register(dto: CredentialsDTO): Observable<unknown> {
+    return this.http.post(this.baseUrl + '/register', dto);
+  }
+
+  public login(dto: CredentialsDTO): Observable<SessionDTO> {
+    return this.http.post<SessionDTO>(this.baseUrl + '/login', dto).pipe(
+      tap(session => localStorage.setItem(TOKEN_KEY, session.token))
+    );
+  }
+
+  public setAvatarFromUrl(url: string): Observable<ProfileDTO> {
+    const formData = new FormData();
+    formData.append('url', url);
+    return this.http.put<ProfileDTO>(this.baseUrl + '/me/avatar', formData, { headers: UserService.authHeaders() });
+  }
+
+  public static authHeaders(): HttpHeaders {
+    const token = localStorage.getItem(TOKEN_KEY);
+    if (token == null) return new HttpHeaders();
+    return new HttpHeaders({ 'Authorization': `Bearer ${token}` });
+  }
+}
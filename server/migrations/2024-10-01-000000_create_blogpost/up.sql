@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS blogpost (
+     id SERIAL PRIMARY KEY,
+     text VARCHAR(2000) NOT NULL,
+     username VARCHAR(128) NOT NULL,
+     published_at TIMESTAMPTZ NOT NULL,
+     avatar VARCHAR(128),
+     postimage VARCHAR(128),
+     edited_at TIMESTAMPTZ
+);
+
+-- databases created by the init scripts before the server managed the schema
+ALTER TABLE blogpost ADD COLUMN IF NOT EXISTS edited_at TIMESTAMPTZ;
+
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'blogpost' AND column_name = 'dateofpublication'
+    ) THEN
+        -- posts that only have the date are treated as published at midnight UTC
+        ALTER TABLE blogpost ADD COLUMN published_at TIMESTAMPTZ;
+        UPDATE blogpost SET published_at = dateofpublication::TIMESTAMP AT TIME ZONE 'UTC';
+        ALTER TABLE blogpost ALTER COLUMN published_at SET NOT NULL;
+        ALTER TABLE blogpost DROP COLUMN dateofpublication;
+    END IF;
+END $$;
+
+CREATE INDEX IF NOT EXISTS idx_blogpost_published_at_id ON blogpost (published_at DESC, id DESC);
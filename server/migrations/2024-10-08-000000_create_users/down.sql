@@ -0,0 +1,3 @@
+ALTER TABLE blogpost DROP COLUMN user_id;
+DROP TABLE session;
+DROP TABLE users;
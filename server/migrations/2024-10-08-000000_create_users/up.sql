@@ -0,0 +1,18 @@
+CREATE TABLE users (
+     id SERIAL PRIMARY KEY,
+     username VARCHAR(128) NOT NULL UNIQUE,
+     password_hash VARCHAR(256) NOT NULL,
+     created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- only the sha256 of the token is stored
+CREATE TABLE session (
+     token_hash VARCHAR(64) PRIMARY KEY,
+     user_id INT4 NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+     expires_at TIMESTAMPTZ NOT NULL
+);
+CREATE INDEX idx_session_user_id ON session (user_id);
+
+-- posts created before user accounts existed do not have an author
+ALTER TABLE blogpost ADD COLUMN user_id INT4 REFERENCES users (id);
+CREATE INDEX idx_blogpost_user_id ON blogpost (user_id);
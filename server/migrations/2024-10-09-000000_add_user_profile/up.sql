@@ -0,0 +1,4 @@
+ALTER TABLE users ADD COLUMN display_name VARCHAR(128);
+ALTER TABLE users ADD COLUMN bio VARCHAR(500);
+-- avatar image uuid, shared by all posts of the user
+ALTER TABLE users ADD COLUMN avatar VARCHAR(128);
@@ -0,0 +1,10 @@
+CREATE TABLE comment (
+    id SERIAL PRIMARY KEY,
+    post_id INT4 NOT NULL REFERENCES blogpost (id) ON DELETE CASCADE,
+    user_id INT4 NOT NULL REFERENCES users (id),
+    text VARCHAR(1000) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- comments of a post are listed from the oldest to the newest
+CREATE INDEX idx_comment_post_id_created_at_id ON comment (post_id, created_at, id);
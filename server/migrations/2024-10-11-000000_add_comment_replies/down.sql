@@ -0,0 +1,6 @@
+DROP INDEX idx_comment_parent_id;
+DROP INDEX idx_comment_root_id;
+ALTER TABLE comment DROP COLUMN deleted_at;
+ALTER TABLE comment DROP COLUMN depth;
+ALTER TABLE comment DROP COLUMN root_id;
+ALTER TABLE comment DROP COLUMN parent_id;
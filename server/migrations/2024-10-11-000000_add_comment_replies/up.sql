@@ -0,0 +1,9 @@
+ALTER TABLE comment ADD COLUMN parent_id INT4 REFERENCES comment (id);
+-- top level comment of the thread, NULL for top level comments
+ALTER TABLE comment ADD COLUMN root_id INT4 REFERENCES comment (id);
+ALTER TABLE comment ADD COLUMN depth INT4 NOT NULL DEFAULT 0;
+-- deleted comments with replies are kept as tombstones
+ALTER TABLE comment ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_comment_root_id ON comment (root_id);
+CREATE INDEX idx_comment_parent_id ON comment (parent_id);
@@ -0,0 +1,9 @@
+CREATE TABLE reaction (
+    id SERIAL PRIMARY KEY,
+    post_id INT4 NOT NULL REFERENCES blogpost (id) ON DELETE CASCADE,
+    user_id INT4 NOT NULL REFERENCES users (id),
+    kind VARCHAR(16) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- a user can react with every kind only once per post
+    CONSTRAINT reaction_post_id_user_id_kind_key UNIQUE (post_id, user_id, kind)
+);
@@ -0,0 +1,2 @@
+DROP TABLE blogpost_tag;
+DROP TABLE tag;
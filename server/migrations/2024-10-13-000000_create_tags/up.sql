@@ -0,0 +1,14 @@
+CREATE TABLE tag (
+    id SERIAL PRIMARY KEY,
+    -- normalized, lowercase
+    name VARCHAR(32) NOT NULL UNIQUE
+);
+
+CREATE TABLE blogpost_tag (
+    post_id INT4 NOT NULL REFERENCES blogpost (id) ON DELETE CASCADE,
+    tag_id INT4 NOT NULL REFERENCES tag (id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+
+-- feed filtered by a tag
+CREATE INDEX idx_blogpost_tag_tag_id ON blogpost_tag (tag_id);
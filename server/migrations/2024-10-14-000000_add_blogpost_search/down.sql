@@ -0,0 +1,2 @@
+DROP INDEX idx_blogpost_search_vector;
+ALTER TABLE blogpost DROP COLUMN search_vector;
@@ -0,0 +1,7 @@
+-- usernames are matched as they are, the text is stemmed
+ALTER TABLE blogpost ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', username), 'A') || setweight(to_tsvector('english', text), 'B')
+    ) STORED;
+
+CREATE INDEX idx_blogpost_search_vector ON blogpost USING GIN (search_vector);
@@ -0,0 +1 @@
+DROP INDEX idx_blogpost_username_published_at_id;
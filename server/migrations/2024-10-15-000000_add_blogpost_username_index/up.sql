@@ -0,0 +1,2 @@
+-- feed of a single author
+CREATE INDEX idx_blogpost_username_published_at_id ON blogpost (username, published_at DESC, id DESC);
@@ -0,0 +1,14 @@
+DROP INDEX idx_blogpost_search_vector;
+ALTER TABLE blogpost DROP COLUMN search_vector;
+
+ALTER TABLE blogpost
+    DROP COLUMN slug,
+    DROP COLUMN title,
+    ALTER COLUMN text TYPE VARCHAR(2000);
+
+ALTER TABLE blogpost ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', username), 'A') || setweight(to_tsvector('english', text), 'B')
+    ) STORED;
+
+CREATE INDEX idx_blogpost_search_vector ON blogpost USING GIN (search_vector);
@@ -0,0 +1,18 @@
+-- the search vector depends on the text, so it has to be recreated
+DROP INDEX idx_blogpost_search_vector;
+ALTER TABLE blogpost DROP COLUMN search_vector;
+
+ALTER TABLE blogpost
+    ALTER COLUMN text TYPE TEXT,
+    ADD COLUMN title VARCHAR(200),
+    ADD COLUMN slug VARCHAR(100) UNIQUE;
+
+-- titles weigh as much as the usernames
+ALTER TABLE blogpost ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(title, '')), 'A')
+        || setweight(to_tsvector('simple', username), 'A')
+        || setweight(to_tsvector('english', text), 'B')
+    ) STORED;
+
+CREATE INDEX idx_blogpost_search_vector ON blogpost USING GIN (search_vector);
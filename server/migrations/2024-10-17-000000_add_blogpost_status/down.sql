@@ -0,0 +1,5 @@
+DROP INDEX idx_blogpost_scheduled_publish_at;
+ALTER TABLE blogpost
+    DROP CONSTRAINT blogpost_scheduled_publish_at,
+    DROP COLUMN publish_at,
+    DROP COLUMN status;
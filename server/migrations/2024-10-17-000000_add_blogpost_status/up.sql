@@ -0,0 +1,9 @@
+-- existing posts are already published
+ALTER TABLE blogpost
+    ADD COLUMN status VARCHAR(16) NOT NULL DEFAULT 'published'
+        CHECK (status IN ('draft', 'scheduled', 'published')),
+    ADD COLUMN publish_at TIMESTAMPTZ,
+    ADD CONSTRAINT blogpost_scheduled_publish_at CHECK (status <> 'scheduled' OR publish_at IS NOT NULL);
+
+-- scheduled posts that are due are looked up periodically
+CREATE INDEX idx_blogpost_scheduled_publish_at ON blogpost (publish_at) WHERE status = 'scheduled';
@@ -0,0 +1,2 @@
+DROP INDEX idx_blogpost_user_id_published_at_id;
+CREATE INDEX idx_blogpost_username_published_at_id ON blogpost (username, published_at DESC, id DESC);
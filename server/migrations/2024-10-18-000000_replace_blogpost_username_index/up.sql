@@ -0,0 +1,3 @@
+-- the author feed matches blogposts by the id of the author's account, not by the username column
+DROP INDEX idx_blogpost_username_published_at_id;
+CREATE INDEX idx_blogpost_user_id_published_at_id ON blogpost (user_id, published_at DESC, id DESC);
@@ -0,0 +1,104 @@
+use std::future::{ready, Ready};
+use actix_web::{
+    body::MessageBody, dev::{Payload, ServiceRequest, ServiceResponse},
+    error::InternalError, http::header::AUTHORIZATION, middleware::Next,
+    web, Error, FromRequest, HttpMessage, HttpRequest, HttpResponse
+};
+use log::{log, Level};
+use crate::{db::DBPool, models::GenericErrorMessageDTO, service::user_service};
+
+/// user the request was authenticated as, put into the request extensions by the authenticate middleware
+///
+/// extracting it from a request without a valid session results in 401,
+/// use Option<AuthenticatedUser> for endpoints where authentication is optional,
+/// there an invalid or expired session is treated as anonymous
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub id: i32,
+    pub username: String,
+    /// token of the session, used for logging out
+    pub token: String,
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let extensions = req.extensions();
+        let user = extensions.get::<AuthenticatedUser>().cloned();
+        let msg = match extensions.get::<InvalidSession>() {
+            Some(_) => "Session is invalid or has expired!",
+            None => "Authentication required!",
+        };
+        ready(user.ok_or_else(|| unauthorized(msg).into()))
+    }
+}
+
+/// marker put into the request extensions by the authenticate middleware when the token did not resolve to a session
+#[derive(Debug, Clone, Copy)]
+struct InvalidSession;
+
+fn unauthorized(msg: &str) -> InternalError<String> {
+    let err_dto = GenericErrorMessageDTO::new(msg.to_string());
+    InternalError::from_response(msg.to_string(), HttpResponse::Unauthorized().json(err_dto))
+}
+
+/// token from the "Authorization: Bearer <token>" header
+fn bearer_token(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(|token| token.trim().to_string())
+}
+
+/// resolves the session token into the user
+///
+/// requests without a token pass through unauthenticated,
+/// requests with an invalid or expired token too, but endpoints requiring authentication reject them with 401
+pub async fn authenticate(
+    req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let Some(token) = bearer_token(&req) else {
+        return next.call(req).await.map(|res| res.map_into_boxed_body());
+    };
+
+    let pool = req.app_data::<web::Data<DBPool>>().cloned();
+    let Some(pool) = pool else {
+        log!(Level::Error, "Missing DB connection pool in the app data");
+        return Ok(req.into_response(HttpResponse::InternalServerError().finish()));
+    };
+
+    let token_clone = token.clone();
+    let user = web::block(move || {
+        let mut conn = pool.get()?;
+        user_service::get_session_user(&mut conn, &token_clone)
+    }).await;
+
+    let user = match user {
+        Ok(Ok(user)) => user,
+        Ok(Err(e)) => {
+            log!(Level::Error, "Error getting session user: {}", crate::unroll_anyhow_result(e));
+            return Ok(req.into_response(HttpResponse::InternalServerError().finish()));
+        }
+        Err(e) => {
+            log!(Level::Error, "Error getting session user: {}", e);
+            return Ok(req.into_response(HttpResponse::InternalServerError().finish()));
+        }
+    };
+
+    let Some(user) = user else {
+        req.extensions_mut().insert(InvalidSession);
+        return next.call(req).await.map(|res| res.map_into_boxed_body());
+    };
+
+    req.extensions_mut().insert(AuthenticatedUser {
+        id: user.id,
+        username: user.username,
+        token,
+    });
+
+    next.call(req).await.map(|res| res.map_into_boxed_body())
+}
@@ -0,0 +1,70 @@
+use std::{env, time::Duration};
+use anyhow::{anyhow, bail, Context, Result};
+use crate::models::ImageFormat;
+
+/// default for MAX_COMMENT_DEPTH
+const DEFAULT_MAX_COMMENT_DEPTH: i32 = 5;
+/// default for PUBLIC_URL
+const DEFAULT_PUBLIC_URL: &str = "http://localhost:8080";
+/// default for PUBLISH_INTERVAL in seconds
+const DEFAULT_PUBLISH_INTERVAL: u64 = 30;
+
+/// optional settings read from the environment, missing ones take the default value
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// how deep replies can be nested, top level comments have depth 0
+    pub max_comment_depth: i32,
+    /// address the server is reachable at, without the trailing slash, used for absolute links in the feeds
+    pub public_url: String,
+    /// how often the scheduled blogposts are checked for publishing
+    pub publish_interval: Duration,
+    /// formats accepted for the post images and avatars
+    pub image_formats: Vec<ImageFormat>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_comment_depth: DEFAULT_MAX_COMMENT_DEPTH,
+            public_url: DEFAULT_PUBLIC_URL.to_string(),
+            publish_interval: Duration::from_secs(DEFAULT_PUBLISH_INTERVAL),
+            image_formats: ImageFormat::ALL.to_vec(),
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let mut config = Config::default();
+
+        if let Ok(max_comment_depth) = env::var("MAX_COMMENT_DEPTH") {
+            config.max_comment_depth = max_comment_depth
+                .parse::<u8>()
+                .context(format!("parsing MAX_COMMENT_DEPTH: {max_comment_depth}"))?
+                .into();
+        }
+
+        if let Ok(public_url) = env::var("PUBLIC_URL") {
+            config.public_url = public_url.trim_end_matches('/').to_string();
+        }
+
+        if let Ok(publish_interval) = env::var("PUBLISH_INTERVAL") {
+            let seconds = publish_interval
+                .parse::<u64>()
+                .context(format!("parsing PUBLISH_INTERVAL: {publish_interval}"))?;
+            if seconds == 0 { bail!("PUBLISH_INTERVAL has to be at least 1 second"); }
+            config.publish_interval = Duration::from_secs(seconds);
+        }
+
+        // comma separated names, e.g. "png,jpeg"
+        if let Ok(image_formats) = env::var("IMAGE_FORMATS") {
+            config.image_formats = image_formats
+                .split(',')
+                .map(|name| ImageFormat::from_name(name).ok_or(anyhow!("unknown image format: {name}")))
+                .collect::<Result<Vec<_>>>()
+                .context(format!("parsing IMAGE_FORMATS: {image_formats}"))?;
+        }
+
+        Ok(config)
+    }
+}
@@ -1,11 +1,26 @@
 use diesel::{r2d2::ConnectionManager, PgConnection};
-use anyhow::{Context, Result};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use anyhow::{anyhow, Context, Result};
 
 pub type DBPool = r2d2::Pool<ConnectionManager<PgConnection>>;
 
+/// migrations from the migrations folder, embedded at compile time
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
 pub fn establish_connection_pool(db_url: String) -> Result<DBPool> {
     let manager = ConnectionManager::<PgConnection>::new(db_url);
     r2d2::Pool::builder()
         .build(manager)
         .context("creating r2d2 pool")
 }
+
+/// applies all pending migrations, returns the versions of the applied migrations
+pub fn run_migrations(pool: &DBPool) -> Result<Vec<String>> {
+    let mut conn = pool.get()
+        .context("getting a connection from pool")?;
+
+    conn.run_pending_migrations(MIGRATIONS)
+        .map(|versions| versions.iter().map(|version| version.to_string()).collect())
+        .map_err(|e| anyhow!(e))
+        .context("running migrations")
+}
@@ -1,36 +1,46 @@
-use std::collections::HashMap;
 use std::time::Duration;
 use actix_multipart::{Field, Multipart};
-use actix_web::{get, post, web, HttpRequest, HttpResponse, Responder};
+use actix_web::{delete, get, post, route, web, HttpRequest, HttpResponse, Responder};
 use futures_util::TryStreamExt;
-use reqwest::Url;
-use serde_json::to_string;
+use serde::de::DeserializeOwned;
 use tokio::time::timeout;
-use crate::models::{FeedDTO, GenericErrorMessageDTO, MAX_TEXT_SIZE, MAX_USERNAME_SIZE};
-use crate::service::blogpost_service::get_blogposts;
-use crate::service::image_service::{delete_image, download_avatar, save_image};
+use crate::models::{
+    normalize_tag, FeedCursor, FeedDTO, FeedQuery, GenericErrorMessageDTO, ImageFormat, StatusQuery, UpdateBlogPostDTO,
+    DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, MAX_TEXT_SIZE, POST_IMAGE_VARIANTS, POST_STATUSES
+};
+use crate::service::blogpost_service::{count_blogposts, get_blogposts, AuthorOnly, FeedFilter, FeedPosition};
+use crate::service::image_service::{delete_image, save_image};
 use crate::{models::CreateBlogPostDTO, service::blogpost_service};
+use crate::auth::AuthenticatedUser;
+use crate::config::Config;
 use crate::db::DBPool;
 use log::{log, Level};
 
 /// helper function that remove blogpost image and avatar if any of them have been saved
 pub async fn clear_files(image: Option<String>, avatar: Option<String>) {
     if let Some(uuid) = image {
-        let res = delete_image(uuid).await;
-        if let Err(e) = res { log!(Level::Error, "Error removing an image: {}", crate::unroll_anyhow_result(e)); }
+        let res = delete_image(uuid.clone()).await;
+        if let Err(e) = res { log!(Level::Error, "Error removing image {}: {}", uuid, crate::unroll_anyhow_result(e)); }
     }
     if let Some(uuid) = avatar {
-        let res = delete_image(uuid).await;
-        if let Err(e) = res { log!(Level::Error, "Error removing an avatar: {}", crate::unroll_anyhow_result(e)); }
+        let res = delete_image(uuid.clone()).await;
+        if let Err(e) = res { log!(Level::Error, "Error removing avatar {}: {}", uuid, crate::unroll_anyhow_result(e)); }
     }
 }
 
+/// largest json payload of the blogpost form, leaves room for the escaped characters in the text
+const MAX_DATA_SIZE: usize = 2 * MAX_TEXT_SIZE;
+
 const CHUNK_TIMEOUT: Duration = Duration::from_millis(500);
 const MAX_CHUNKS: u32 = 20;
 /// used when draining the leftover data before early return
 pub async fn drain_data(payload: &mut Multipart, field: &mut Field) {
     while let Ok(Some(_bytes)) = field.try_next().await {}
+    drain_fields(payload).await;
+}
 
+/// drains the fields after the current one, used when the current field has been read to the end
+pub async fn drain_fields(payload: &mut Multipart) {
     let mut chunk_count = 0;
     while chunk_count < MAX_CHUNKS {
         match timeout(CHUNK_TIMEOUT, payload.try_next()).await {
@@ -43,10 +53,17 @@ pub async fn drain_data(payload: &mut Multipart, field: &mut Field) {
     }
 }
 
-#[post("/api/v1/blogpost")]
-async fn create_blogpost(mut payload: Multipart, pool: web::Data<DBPool>) -> impl Responder {
-    let mut data_payload: Option<CreateBlogPostDTO> = None;
-    let mut avatar_uuid: Option<String> = None;
+/// reads the multipart form shared by creating and updating a blogpost,
+/// json payload is expected in the "data" field and an optional post image in the "image" field
+///
+/// `fits` checks if the json payload is within the size limits
+///
+/// on error already saved post image is deleted and the response that should be returned is provided
+async fn read_blogpost_form<T: DeserializeOwned>(
+    payload: &mut Multipart,
+    image_formats: &[ImageFormat],
+    fits: fn(&T) -> bool) -> Result<(Option<T>, Option<String>), HttpResponse> {
+    let mut data_payload: Option<T> = None;
     let mut post_image_uuid: Option<String> = None;
 
     // read the incoming data
@@ -59,154 +76,265 @@ async fn create_blogpost(mut payload: Multipart, pool: web::Data<DBPool>) -> imp
     while let Ok(Some(mut field)) = payload.try_next().await {
         let content_disposition = field.content_disposition();
         if content_disposition.is_none() {
-            clear_files(post_image_uuid, avatar_uuid).await;
-            drain_data(&mut payload, &mut field).await;
-            return HttpResponse::BadRequest().force_close().finish();
+            clear_files(post_image_uuid, None).await;
+            drain_data(payload, &mut field).await;
+            return Err(HttpResponse::BadRequest().force_close().finish());
         }
         let content_disposition = content_disposition.unwrap();
 
         let field_name = content_disposition.get_name();
         if field_name.is_none() {
-            clear_files(post_image_uuid, avatar_uuid).await;
-            drain_data(&mut payload, &mut field).await;
-            return HttpResponse::BadRequest().force_close().finish();
+            clear_files(post_image_uuid, None).await;
+            drain_data(payload, &mut field).await;
+            return Err(HttpResponse::BadRequest().force_close().finish());
         }
         let field_name = field_name.unwrap();
 
         match field_name {
             "data" => {
-                let bytes = field.try_next().await;
-                if let Err(e) = bytes {
-                    log!(Level::Error, "Error reading data from the body: {}", e);
-                    clear_files(post_image_uuid, avatar_uuid).await;
-                    drain_data(&mut payload, &mut field).await;
-                    return HttpResponse::InternalServerError().force_close().finish();
+                // long texts arrive in more than one chunk
+                let mut bytes = Vec::new();
+                loop {
+                    let chunk = field.try_next().await;
+                    if let Err(e) = chunk {
+                        log!(Level::Error, "Error reading data from the body: {}", e);
+                        clear_files(post_image_uuid, None).await;
+                        drain_data(payload, &mut field).await;
+                        return Err(HttpResponse::InternalServerError().force_close().finish());
+                    }
+                    let Some(chunk) = chunk.unwrap() else { break };
+
+                    bytes.extend_from_slice(&chunk);
+                    if bytes.len() > MAX_DATA_SIZE {
+                        clear_files(post_image_uuid, None).await;
+                        drain_data(payload, &mut field).await;
+                        return Err(HttpResponse::PayloadTooLarge().force_close().finish());
+                    }
                 }
-                let bytes = bytes.unwrap();
-                if bytes.is_none() {
-                    clear_files(post_image_uuid, avatar_uuid).await;
-                    drain_data(&mut payload, &mut field).await;
-                    return HttpResponse::BadRequest().force_close().finish();
+                if bytes.is_empty() {
+                    clear_files(post_image_uuid, None).await;
+                    drain_fields(payload).await;
+                    return Err(HttpResponse::BadRequest().force_close().finish());
                 }
-                let bytes = bytes.unwrap();
 
                 let deser_data = serde_json::from_slice(&bytes);
                 if deser_data.is_err() {
-                    clear_files(post_image_uuid, avatar_uuid).await;
-                    drain_data(&mut payload, &mut field).await;
-                    return HttpResponse::BadRequest().force_close().finish();
+                    clear_files(post_image_uuid, None).await;
+                    drain_fields(payload).await;
+                    return Err(HttpResponse::BadRequest().force_close().finish());
                 }
-                let deser_data: CreateBlogPostDTO = deser_data.unwrap();
+                let deser_data: T = deser_data.unwrap();
 
-                if deser_data.text.len() > MAX_TEXT_SIZE || deser_data.username.len() > MAX_USERNAME_SIZE {
-                    clear_files(post_image_uuid, avatar_uuid).await;
-                    drain_data(&mut payload, &mut field).await;
-                    return HttpResponse::PayloadTooLarge().force_close().finish();
+                if !fits(&deser_data) {
+                    clear_files(post_image_uuid, None).await;
+                    drain_fields(payload).await;
+                    return Err(HttpResponse::PayloadTooLarge().force_close().finish());
                 }
 
                 data_payload = Some(deser_data);
             }
 
             "image" => {
-                let image_result = save_image(&mut field).await;
+                let image_result = save_image(&mut field, image_formats, &POST_IMAGE_VARIANTS).await;
                 if let Err(e) = image_result {
                     log!(Level::Error, "Error saving an image: {}", crate::unroll_anyhow_result(e));
-                    clear_files(post_image_uuid, avatar_uuid).await;
-                    drain_data(&mut payload, &mut field).await;
-                    return HttpResponse::InternalServerError().force_close().finish();
+                    clear_files(post_image_uuid, None).await;
+                    drain_fields(payload).await;
+                    return Err(HttpResponse::InternalServerError().force_close().finish());
                 } else {
-                    let (image_result, too_large, is_png) = image_result.unwrap();
-                    if !too_large && is_png { post_image_uuid = Some(image_result); }
-                    else if !is_png {
-                        clear_files(post_image_uuid, avatar_uuid).await;
-                        drain_data(&mut payload, &mut field).await;
-                        return HttpResponse::BadRequest().force_close().finish();
+                    let (image_result, too_large, is_allowed) = image_result.unwrap();
+                    if !too_large && is_allowed { post_image_uuid = Some(image_result); }
+                    else if !is_allowed {
+                        clear_files(post_image_uuid, None).await;
+                        drain_fields(payload).await;
+                        return Err(HttpResponse::BadRequest().force_close().finish());
                     }
                     else {
-                        clear_files(post_image_uuid, avatar_uuid).await;
-                        drain_data(&mut payload, &mut field).await;
-                        return HttpResponse::PayloadTooLarge().force_close().finish();
+                        clear_files(post_image_uuid, None).await;
+                        drain_fields(payload).await;
+                        return Err(HttpResponse::PayloadTooLarge().force_close().finish());
                     }
                 }
             }
 
-            _ => return HttpResponse::BadRequest().force_close().finish()
+            _ => {
+                clear_files(post_image_uuid, None).await;
+                drain_data(payload, &mut field).await;
+                return Err(HttpResponse::BadRequest().force_close().finish());
+            }
         }
     }
 
+    Ok((data_payload, post_image_uuid))
+}
+
+const INVALID_STATUS_MESSAGE: &str = "Status has to be draft, scheduled or published, only scheduled posts have a publish_at time!";
+
+/// creates a blogpost authored by the authenticated user
+#[post("/api/v1/blogpost")]
+async fn create_blogpost(
+    user: AuthenticatedUser,
+    mut payload: Multipart,
+    pool: web::Data<DBPool>,
+    config: web::Data<Config>) -> impl Responder {
+    let form = read_blogpost_form(&mut payload, &config.image_formats, CreateBlogPostDTO::fits).await;
+    let (data_payload, post_image_uuid) = match form {
+        Ok(form) => form,
+        Err(response) => return response,
+    };
 
     // post data cannot be missing
     if data_payload.is_none() {
-        clear_files(post_image_uuid, avatar_uuid).await;
+        clear_files(post_image_uuid, None).await;
         return HttpResponse::BadRequest().finish();
     }
     let data_payload = data_payload.unwrap();
 
-    // download avatar
-    if data_payload.avatar.is_some() {
-        match Url::parse(data_payload.avatar.as_ref().unwrap()) {
-            Err(_) => {
-                clear_files(post_image_uuid, avatar_uuid).await;
-                let err_dto = GenericErrorMessageDTO::new("Image must be a PGN and not larger than 2MB!".to_string());
-                return HttpResponse::BadRequest().json(err_dto);
-            },
-            _ => {}
-        }
+    let tags = data_payload.normalized_tags();
+    if tags.is_none() {
+        clear_files(post_image_uuid, None).await;
+        let err_dto = GenericErrorMessageDTO::new(
+            "Post can have up to 10 tags made of up to 32 letters, digits, '_' or '-'!".to_string());
+        return HttpResponse::BadRequest().json(err_dto);
+    }
+    let tags = tags.unwrap();
 
-        let res = download_avatar(data_payload.avatar.as_ref().unwrap()).await;
-        if let Err(e) = res {
-            clear_files(post_image_uuid, avatar_uuid).await;
-            log!(Level::Error, "Error downloading avatar: {}", crate::unroll_anyhow_result(e));
-            return HttpResponse::InternalServerError().finish();
-        }
-        let res = res.unwrap();
-        if let Some(avatar) = res { avatar_uuid = Some(avatar); }
-        else {
-            clear_files(post_image_uuid, avatar_uuid).await;
-            let err_dto = GenericErrorMessageDTO::new("Image must be a PGN and not larger than 2MB!".to_string());
-            return HttpResponse::BadRequest().json(err_dto);
-        }
+    if !data_payload.has_valid_status() {
+        clear_files(post_image_uuid, None).await;
+        let err_dto = GenericErrorMessageDTO::new(INVALID_STATUS_MESSAGE.to_string());
+        return HttpResponse::BadRequest().json(err_dto);
     }
 
     let conn = pool.get();
     if let Err(e) = conn {
         log!(Level::Error, "Error getting a connection from pool: {}", e);
-        clear_files(post_image_uuid, avatar_uuid).await;
+        clear_files(post_image_uuid, None).await;
         return HttpResponse::InternalServerError().finish();
     }
     let mut conn = conn.unwrap();
 
     // persist the post data
-    let avatar_uuid_clone = avatar_uuid.clone();
     let post_image_uuid_clone = post_image_uuid.clone();
     let res = web::block(move ||
-        blogpost_service::create_blogpost(&mut conn, data_payload, avatar_uuid_clone, post_image_uuid_clone)
+        blogpost_service::create_blogpost(&mut conn, data_payload, tags, &user, post_image_uuid_clone)
         ).await;
     if let Err(e) = res {
         log!(Level::Error, "Error saving blogpost into the db: {}", e);
-        clear_files(post_image_uuid, avatar_uuid).await;
+        clear_files(post_image_uuid, None).await;
         return HttpResponse::InternalServerError().finish();
-    } else if let Err(e) = res.unwrap() {
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
         log!(Level::Error, "Error saving blogpost into the db: {}", crate::unroll_anyhow_result(e));
-        clear_files(post_image_uuid, avatar_uuid).await;
+        clear_files(post_image_uuid, None).await;
         return HttpResponse::InternalServerError().finish();
     }
 
-
-    HttpResponse::Created().finish()
+    HttpResponse::Created().json(res.unwrap())
 }
 
 
+/// returns the page of the feed requested by the query string, the tag from the query is added to the filter
+async fn feed_response(req: &HttpRequest, pool: &DBPool, mut filter: FeedFilter) -> HttpResponse {
+    let params = web::Query::<FeedQuery>::from_query(req.query_string());
+    if params.is_err() { return HttpResponse::BadRequest().finish(); }
+    let params = params.unwrap().into_inner();
+
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) { return HttpResponse::BadRequest().finish(); }
+
+    // exactly one of page and cursor has to be present
+    let position = match (params.page, params.cursor) {
+        (Some(page), None) => {
+            if page < 1 { return HttpResponse::BadRequest().finish(); }
+            FeedPosition::Page(page)
+        }
+        (None, Some(cursor)) => {
+            let cursor = FeedCursor::decode(&cursor);
+            if cursor.is_err() { return HttpResponse::BadRequest().finish(); }
+            FeedPosition::After(cursor.unwrap())
+        }
+        _ => return HttpResponse::BadRequest().finish(),
+    };
+
+    if let Some(tag) = params.tag {
+        let tag = normalize_tag(&tag);
+        if tag.is_none() { return HttpResponse::BadRequest().finish(); }
+        filter.tag = tag;
+    }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let page = match position {
+        FeedPosition::Page(page) => Some(page),
+        FeedPosition::After(_) => None,
+    };
+
+    let res = web::block(move || -> anyhow::Result<_> {
+        let (blogposts, next_cursor) = get_blogposts(&mut conn, position, &filter, limit)?;
+        let total_count = count_blogposts(&mut conn, &filter)?;
+        Ok((blogposts, next_cursor, total_count))
+    }).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting blogposts: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting blogposts: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+    let (blogposts, next_cursor, total_count) = res.unwrap();
+
+    let dto = FeedDTO::new(blogposts, next_cursor, page, limit, total_count);
+    HttpResponse::Ok().json(dto)
+}
+
 #[get("/api/v1/blogpost")]
 async fn get_feed(req: HttpRequest, pool: web::Data<DBPool>) -> impl Responder {
-    let params = web::Query::<HashMap<String, u32>>::from_query(req.query_string());
-    if let Err(_) = params { return HttpResponse::BadRequest().finish(); }
-    let params = params.unwrap();
+    feed_response(&req, &pool, FeedFilter::default()).await
+}
+
+/// returns the blogposts of a single author, paginated the same way as the feed
+#[get("/api/v1/users/{username}/blogposts")]
+async fn get_author_feed(req: HttpRequest, username: web::Path<String>, pool: web::Data<DBPool>) -> impl Responder {
+    let filter = FeedFilter {
+        username: Some(username.into_inner()),
+        ..FeedFilter::default()
+    };
+    feed_response(&req, &pool, filter).await
+}
+
+/// returns the blogposts of the authenticated user including drafts and scheduled posts,
+/// optionally filtered by `status`
+#[get("/api/v1/users/me/blogposts")]
+async fn get_own_blogposts(user: AuthenticatedUser, req: HttpRequest, pool: web::Data<DBPool>) -> impl Responder {
+    let params = web::Query::<StatusQuery>::from_query(req.query_string());
+    if params.is_err() { return HttpResponse::BadRequest().finish(); }
+    let params = params.unwrap().into_inner();
 
-    let page_str = params.get("page");
-    if page_str.is_none() { return HttpResponse::BadRequest().finish(); }
-    let page_num = page_str.unwrap();
-    if *page_num < 1 { return HttpResponse::BadRequest().finish(); }
+    if let Some(status) = &params.status {
+        if !POST_STATUSES.contains(&status.as_str()) { return HttpResponse::BadRequest().finish(); }
+    }
+
+    let filter = FeedFilter {
+        username: Some(user.username),
+        status: params.status,
+        ..FeedFilter::default()
+    };
+    feed_response(&req, &pool, filter).await
+}
+
+/// returns a single blogpost, 404 if there is no blogpost with the given id
+/// drafts and scheduled posts are returned only to their author
+#[get("/api/v1/blogpost/{id}")]
+async fn get_blogpost(user: Option<AuthenticatedUser>, post_id: web::Path<i32>, pool: web::Data<DBPool>) -> impl Responder {
+    let post_id = post_id.into_inner();
 
     let conn = pool.get();
     if let Err(e) = conn {
@@ -215,19 +343,179 @@ async fn get_feed(req: HttpRequest, pool: web::Data<DBPool>) -> impl Responder {
     }
     let mut conn = conn.unwrap();
 
-    let blogposts = get_blogposts(&mut conn, *page_num);
-    if let Err(e) = blogposts {
-        log!(Level::Error, "Error getting blogposts, page {}: {}", page_num, crate::unroll_anyhow_result(e));
+    let res = web::block(move || blogpost_service::get_blogpost(&mut conn, post_id)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting blogpost {}: {}", post_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
         return HttpResponse::InternalServerError().finish();
     }
-    let blogposts = blogposts.unwrap();
-    let dto = FeedDTO::new(blogposts);
 
-    let response_body = to_string(&dto);
-    if let Err(e) = response_body {
-        log!(Level::Error, "Error serializing feed dto: {}", e);
+    let user_id = user.map(|user| user.id);
+    match res.unwrap() {
+        Some(post) if post.is_visible_to(user_id) => HttpResponse::Ok().json(post),
+        _ => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {post_id} does not exist!"));
+            HttpResponse::NotFound().json(err_dto)
+        }
+    }
+}
+
+/// returns a single blogpost by its slug, 404 if there is no blogpost with the given slug
+/// drafts and scheduled posts are returned only to their author
+#[get("/api/v1/blogpost/by-slug/{slug}")]
+async fn get_blogpost_by_slug(
+    user: Option<AuthenticatedUser>,
+    slug: web::Path<String>,
+    pool: web::Data<DBPool>) -> impl Responder {
+    let slug = slug.into_inner();
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
         return HttpResponse::InternalServerError().finish();
     }
+    let mut conn = conn.unwrap();
+
+    let slug_clone = slug.clone();
+    let res = web::block(move || blogpost_service::get_blogpost_by_slug(&mut conn, &slug_clone)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting blogpost {}: {}", slug, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting blogpost {}: {}", slug, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    let user_id = user.map(|user| user.id);
+    match res.unwrap() {
+        Some(post) if post.is_visible_to(user_id) => HttpResponse::Ok().json(post),
+        _ => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {slug} does not exist!"));
+            HttpResponse::NotFound().json(err_dto)
+        }
+    }
+}
+
+/// replaces the title, the text and/or the image of a blogpost, accepts the same multipart form as create_blogpost
+/// old image is deleted once the new one is persisted, only the author can update the blogpost
+#[route("/api/v1/blogpost/{id}", method = "PUT", method = "PATCH")]
+async fn update_blogpost(
+    user: AuthenticatedUser,
+    post_id: web::Path<i32>,
+    mut payload: Multipart,
+    pool: web::Data<DBPool>,
+    config: web::Data<Config>) -> impl Responder {
+    let post_id = post_id.into_inner();
+
+    let form = read_blogpost_form(&mut payload, &config.image_formats, UpdateBlogPostDTO::fits).await;
+    let (data_payload, post_image_uuid) = match form {
+        Ok(form) => form,
+        Err(response) => return response,
+    };
+
+    // there has to be something to update
+    if data_payload.is_none() && post_image_uuid.is_none() {
+        return HttpResponse::BadRequest().finish();
+    }
+    let data_payload = data_payload.unwrap_or_default();
+
+    if !data_payload.has_valid_status() {
+        clear_files(post_image_uuid, None).await;
+        let err_dto = GenericErrorMessageDTO::new(INVALID_STATUS_MESSAGE.to_string());
+        return HttpResponse::BadRequest().json(err_dto);
+    }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        clear_files(post_image_uuid, None).await;
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let post_image_uuid_clone = post_image_uuid.clone();
+    let res = web::block(move ||
+        blogpost_service::update_blogpost(&mut conn, post_id, user.id, data_payload, post_image_uuid_clone)
+        ).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error updating blogpost {}: {}", post_id, e);
+        clear_files(post_image_uuid, None).await;
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error updating blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
+        clear_files(post_image_uuid, None).await;
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    let (post, replaced_image) = match res.unwrap() {
+        AuthorOnly::Done(res) => res,
+        AuthorOnly::NotFound => {
+            clear_files(post_image_uuid, None).await;
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {post_id} does not exist!"));
+            return HttpResponse::NotFound().json(err_dto);
+        }
+        AuthorOnly::NotAuthor => {
+            clear_files(post_image_uuid, None).await;
+            let err_dto = GenericErrorMessageDTO::new("Only the author can edit the blogpost!".to_string());
+            return HttpResponse::Forbidden().json(err_dto);
+        }
+    };
+
+    if let Some(uuid) = replaced_image {
+        if let Err(e) = delete_image(uuid).await {
+            log!(Level::Error, "Error removing a replaced image: {}", crate::unroll_anyhow_result(e));
+        }
+    }
+
+    HttpResponse::Ok().json(post)
+}
+
+/// deletes the blogpost together with its image and avatar, only the author can delete the blogpost
+#[delete("/api/v1/blogpost/{id}")]
+async fn delete_blogpost(user: AuthenticatedUser, post_id: web::Path<i32>, pool: web::Data<DBPool>) -> impl Responder {
+    let post_id = post_id.into_inner();
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || blogpost_service::delete_blogpost(&mut conn, post_id, user.id)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error deleting blogpost {}: {}", post_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error deleting blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    let (post_image, avatar) = match res.unwrap() {
+        AuthorOnly::Done(files) => files,
+        AuthorOnly::NotFound => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {post_id} does not exist!"));
+            return HttpResponse::NotFound().json(err_dto);
+        }
+        AuthorOnly::NotAuthor => {
+            let err_dto = GenericErrorMessageDTO::new("Only the author can delete the blogpost!".to_string());
+            return HttpResponse::Forbidden().json(err_dto);
+        }
+    };
+
+    // row is gone, files marked for deletion can be removed,
+    // the ones that fail are logged and deleted later by the publisher
+    clear_files(post_image, avatar).await;
 
-    HttpResponse::Ok().json(response_body.unwrap())
+    HttpResponse::NoContent().finish()
 }
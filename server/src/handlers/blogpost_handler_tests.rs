@@ -5,16 +5,27 @@ mod tests {
         fs::File,
         io::{Read, Write}
     };
-    use actix_web::{body::MessageBody, test, web::{self, Data}, App};
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web::{self, Data}, App};
     use anyhow::Result;
-    use serde_json::{from_str, to_string};
+    use chrono::DateTime;
+    use serde_json::{to_string, Value};
+    use uuid::Uuid;
     use crate::{
+        auth::authenticate,
+        config::Config,
         db::establish_connection_pool,
-        handlers::blogpost_handler::{create_blogpost, get_feed},
-        models::{CreateBlogPostDTO, FeedDTO}};
-    use diesel::{PgConnection, RunQueryDsl};
+        handlers::{
+            blogpost_handler::{
+                create_blogpost, delete_blogpost, get_author_feed, get_blogpost, get_blogpost_by_slug, get_feed, get_own_blogposts,
+                update_blogpost
+            },
+            test_utils::create_user_session},
+        models::{BlogPost, CreateBlogPostDTO, FeedDTO, UpdateBlogPostDTO, DEFAULT_PAGE_SIZE, EXCERPT_SIZE, MAX_TEXT_SIZE},
+        schema::BlogPostTable,
+        service::blogpost_service::{self, FeedFilter, FeedPosition}};
+    use diesel::{ExpressionMethods, PgConnection, QueryDsl, RunQueryDsl};
 
-    /// TESTS NEED TO BE RAN SEQUENTIALLY
+    // TESTS NEED TO BE RAN SEQUENTIALLY
 
     /// helper function to manually construct a multipart form payload
     fn create_multipart(dto: String, image_file: &mut File) -> Vec<u8> {
@@ -48,8 +59,24 @@ mod tests {
         body
     }
 
+    /// helper function to manually construct a multipart form payload without a post image
+    fn create_data_multipart(dto: String) -> Vec<u8> {
+        let boundary = "my_boundary";
+        let mut body = Vec::new();
+
+        write!(
+            &mut body,
+            "--{}\r\n\
+            Content-Disposition: form-data; name=\"data\"\r\n\r\n\
+        {}\r\n\
+        --{}--\r\n",
+        boundary, dto, boundary).unwrap();
+
+        body
+    }
+
     fn delete_all_posts(conn: &mut PgConnection) -> Result<()> {
-        use crate::schema::blogpost::blogpost::table as BlogpostTable;
+        use crate::schema::blogpost::table as BlogpostTable;
         diesel::delete(BlogpostTable).execute(conn)?;
         Ok(())
     }
@@ -69,6 +96,7 @@ mod tests {
 
         let app = test::init_service(
             App::new()
+            .wrap(from_fn(authenticate))
             .app_data(Data::new(connection_pool.clone()))
             .service(get_feed)
         ).await;
@@ -80,15 +108,8 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_success());
 
-        let body_bytes = resp
-            .into_body()
-            .try_into_bytes()
-            .expect("reading response bytes")
-            .to_vec();
-        let body = String::from_utf8(body_bytes).expect("reading response bytes as string");
-        let unescaped_body = from_str::<String>(&body).expect("reading unsecaped body");
-        let feed: FeedDTO = from_str(&unescaped_body).expect("parsing feed body");
-        assert!(feed.blogposts.len() == 0);
+        let feed: FeedDTO = test::read_body_json(resp).await;
+        assert!(feed.blogposts.is_empty());
     }
 
     #[actix_web::test]
@@ -100,6 +121,7 @@ mod tests {
 
         let app = test::init_service(
             App::new()
+            .wrap(from_fn(authenticate))
             .app_data(Data::new(connection_pool.clone()))
             .service(get_feed)
         ).await;
@@ -121,6 +143,7 @@ mod tests {
 
         let app = test::init_service(
             App::new()
+            .wrap(from_fn(authenticate))
             .app_data(Data::new(connection_pool.clone()))
             .service(get_feed)
         ).await;
@@ -140,27 +163,38 @@ mod tests {
         let connection_pool = establish_connection_pool(db_url)
             .expect("making a connection pool");
 
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
         let app = test::init_service(
             App::new()
+            .wrap(from_fn(authenticate))
             .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
             .service(create_blogpost)
             .service(get_feed)
         ).await;
 
         let dto = CreateBlogPostDTO {
             text: "Hello!".to_string(),
-            username: "admin".to_string(),
-            avatar: Some("https://w7.pngwing.com/pngs/114/579/png-transparent-pink-cross-stroke-ink-brush-pen-red-ink-brush-ink-leave-the-material-text.png".to_string()),
+            title: None,
+            tags: Vec::new(),
+            status: None,
+            publish_at: None,
         };
         let dto_str = to_string(&dto).expect("turning dto to json string");
 
-        let mut image_file = File::open(format!("images/placeholder_avatar"))
+        let mut image_file = File::open("images/placeholder_avatar")
             .expect("opening placeholder avatar");
 
         let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
             .await.unwrap();
 
         let req = test::TestRequest::post()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
             .set_payload(form)
             .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
             .uri("/api/v1/blogpost")
@@ -177,18 +211,16 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_success());
 
-        let body_bytes = resp
-            .into_body()
-            .try_into_bytes()
-            .expect("reading response bytes")
-            .to_vec();
-        let body = String::from_utf8(body_bytes).expect("reading response bytes as string");
-        let unescaped_body = from_str::<String>(&body).expect("reading unsecaped body");
-        let feed: FeedDTO = from_str(&unescaped_body).expect("parsing feed body");
+        // feed items leave out the text and the HTML
+        let feed: Value = test::read_body_json(resp).await;
+        assert!(feed["blogposts"][0].get("text").is_none());
+        assert!(feed["blogposts"][0].get("html").is_none());
+        let feed: FeedDTO = serde_json::from_value(feed).expect("parsing the feed");
         assert!(feed.blogposts.len() == 1);
-        assert_eq!(feed.blogposts[0].text, "Hello!");
-        assert_eq!(feed.blogposts[0].username, "admin");
-        assert!(feed.blogposts[0].avatar.is_some());
+        assert_eq!(feed.blogposts[0].excerpt.0, "Hello!");
+        assert_eq!(feed.blogposts[0].username, user.username);
+        // the author has no profile avatar
+        assert!(feed.blogposts[0].avatar.is_none());
     }
 
     #[actix_web::test]
@@ -200,24 +232,31 @@ mod tests {
 
         let mut conn = connection_pool.get().expect("getting connection");
 
-        let _ = web::block(move || { delete_all_posts(&mut conn) })
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            create_user_session(&mut conn)
+        })
             .await
-            .expect("deleting all posts");
+            .expect("running blocking task")
+            .expect("creating a user session");
 
         let app = test::init_service(
             App::new()
+            .wrap(from_fn(authenticate))
             .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
             .service(create_blogpost)
             .service(get_feed)
         ).await;
 
-        let mut image_file = File::open(format!("images/placeholder_avatar"))
+        let mut image_file = File::open("images/placeholder_avatar")
             .expect("opening placeholder avatar");
 
         let form = web::block(move || {create_multipart("test".to_string(), &mut image_file)})
             .await.unwrap();
 
         let req = test::TestRequest::post()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
             .set_payload(form)
             .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
             .uri("/api/v1/blogpost")
@@ -234,19 +273,63 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_success());
 
-        let body_bytes = resp
-            .into_body()
-            .try_into_bytes()
-            .expect("reading response bytes")
-            .to_vec();
-        let body = String::from_utf8(body_bytes).expect("reading response bytes as string");
-        let unescaped_body = from_str::<String>(&body).expect("reading unsecaped body");
-        let feed: FeedDTO = from_str(&unescaped_body).expect("parsing feed body");
-        assert!(feed.blogposts.len() == 0);
+        let feed: FeedDTO = test::read_body_json(resp).await;
+        assert!(feed.blogposts.is_empty());
     }
 
     #[actix_web::test]
-    async fn test_invalid_blogpost_bad_avatar() {
+    async fn test_get_existing_blogpost() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (user, post_id) = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            let user = create_user_session(&mut conn)?;
+            let dto = CreateBlogPostDTO {
+                text: "Hello!".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &user, None)?;
+            blogpost_service::get_blogposts(&mut conn, FeedPosition::Page(1), &FeedFilter::default(), DEFAULT_PAGE_SIZE)
+                .map(|(posts, _)| (user, posts[0].id))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a blogpost");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(get_blogpost)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let body: Value = test::read_body_json(resp).await;
+        let published_at = body["published_at"].as_str().expect("publication time is a string");
+        assert!(DateTime::parse_from_rfc3339(published_at).is_ok());
+
+        let post: BlogPost = serde_json::from_value(body).expect("parsing blogpost");
+        assert_eq!(post.id, post_id);
+        assert_eq!(post.text, "Hello!");
+        assert_eq!(post.username, user.username);
+        assert_eq!(post.user_id, Some(user.id));
+    }
+
+    #[actix_web::test]
+    async fn test_get_nonexistent_blogpost() {
         let db_url = env::var("DB_URL")
             .expect("missing DB_URL in env");
         let connection_pool = establish_connection_pool(db_url)
@@ -260,49 +343,881 @@ mod tests {
 
         let app = test::init_service(
             App::new()
+            .wrap(from_fn(authenticate))
             .app_data(Data::new(connection_pool.clone()))
+            .service(get_blogpost)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/blogpost/1")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let body: Value = test::read_body_json(resp).await;
+        assert!(body["error"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn test_update_blogpost() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            create_user_session(&mut conn)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
             .service(create_blogpost)
+            .service(get_blogpost)
+            .service(update_blogpost)
+        ).await;
+
+        let dto = CreateBlogPostDTO {
+            text: "Hello!".to_string(),
+            title: None,
+            tags: Vec::new(),
+            status: None,
+            publish_at: None,
+        };
+        let dto_str = to_string(&dto).expect("turning dto to json string");
+
+        let mut image_file = File::open("images/placeholder_avatar")
+            .expect("opening placeholder avatar");
+
+        let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
+            .await.unwrap();
+
+        let req = test::TestRequest::post()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_payload(form)
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .uri("/api/v1/blogpost")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let created = web::block(move || blogpost_service::get_blogposts(&mut conn, FeedPosition::Page(1), &FeedFilter::default(), DEFAULT_PAGE_SIZE))
+            .await
+            .expect("running blocking task")
+            .expect("getting blogposts")
+            .0
+            .remove(0);
+        let old_image = created.post_image.clone().expect("post image is present");
+        assert!(created.edited_at.is_none());
+
+        let dto = UpdateBlogPostDTO {
+            text: Some("Hello again!".to_string()),
+            title: None,
+            status: None,
+            publish_at: None,
+        };
+        let dto_str = to_string(&dto).expect("turning dto to json string");
+
+        let mut image_file = File::open("images/placeholder_avatar")
+            .expect("opening placeholder avatar");
+
+        let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
+            .await.unwrap();
+
+        let req = test::TestRequest::patch()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_payload(form)
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .uri(&format!("/api/v1/blogpost/{}", created.id))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let updated: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(updated.id, created.id);
+        assert_eq!(updated.text, "Hello again!");
+        assert_eq!(updated.username, user.username);
+        assert!(updated.edited_at.is_some());
+        assert!(updated.post_image.is_some());
+        assert_ne!(updated.post_image, created.post_image);
+
+        // replaced image has to be removed
+        assert!(File::open(format!("images/{old_image}")).is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_update_nonexistent_blogpost() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            create_user_session(&mut conn)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(update_blogpost)
+        ).await;
+
+        let dto = UpdateBlogPostDTO {
+            text: Some("Hello again!".to_string()),
+            title: None,
+            status: None,
+            publish_at: None,
+        };
+        let dto_str = to_string(&dto).expect("turning dto to json string");
+
+        let mut image_file = File::open("images/placeholder_avatar")
+            .expect("opening placeholder avatar");
+
+        let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
+            .await.unwrap();
+
+        let req = test::TestRequest::put()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_payload(form)
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .uri("/api/v1/blogpost/1")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_delete_blogpost() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            create_user_session(&mut conn)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(get_blogpost)
+            .service(delete_blogpost)
+        ).await;
+
+        let dto = CreateBlogPostDTO {
+            text: "Hello!".to_string(),
+            title: None,
+            tags: Vec::new(),
+            status: None,
+            publish_at: None,
+        };
+        let dto_str = to_string(&dto).expect("turning dto to json string");
+
+        let mut image_file = File::open("images/placeholder_avatar")
+            .expect("opening placeholder avatar");
+
+        let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
+            .await.unwrap();
+
+        let req = test::TestRequest::post()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_payload(form)
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .uri("/api/v1/blogpost")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let created = web::block(move || blogpost_service::get_blogposts(&mut conn, FeedPosition::Page(1), &FeedFilter::default(), DEFAULT_PAGE_SIZE))
+            .await
+            .expect("running blocking task")
+            .expect("getting blogposts")
+            .0
+            .remove(0);
+        let image = created.post_image.clone().expect("post image is present");
+
+        let req = test::TestRequest::delete()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .uri(&format!("/api/v1/blogpost/{}", created.id))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        // both the row and the image have to be gone
+        assert!(File::open(format!("images/{image}")).is_err());
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{}", created.id))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_delete_nonexistent_blogpost() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            create_user_session(&mut conn)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(delete_blogpost)
+        ).await;
+
+        let req = test::TestRequest::delete()
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .uri("/api/v1/blogpost/1")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_feed_cursor_pagination() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            let user = create_user_session(&mut conn)?;
+            for i in 0..3 {
+                let dto = CreateBlogPostDTO {
+                    text: format!("Post {i}"),
+                    title: None,
+                    tags: Vec::new(),
+                    status: None,
+                    publish_at: None,
+                };
+                blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &user, None)?;
+            }
+            Ok::<_, anyhow::Error>(user)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating blogposts");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(get_feed)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/blogpost?page=1&limit=2")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let first_page = test::read_body_json::<FeedDTO, _>(resp).await;
+        assert_eq!(first_page.blogposts.len(), 2);
+        assert_eq!(first_page.blogposts[0].excerpt.0, "Post 2");
+        assert_eq!(first_page.blogposts[1].excerpt.0, "Post 1");
+        assert_eq!(first_page.page, Some(1));
+        assert_eq!(first_page.page_size, 2);
+        assert_eq!(first_page.total_count, 3);
+        assert!(first_page.has_next);
+        assert!(!first_page.has_previous);
+        let cursor = first_page.next_cursor.expect("next cursor is present");
+
+        // post created after the first page was fetched must not shift the next page
+        let mut conn = connection_pool.get().expect("getting connection");
+        web::block(move || {
+            let dto = CreateBlogPostDTO {
+                text: "Post 3".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &user, None)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a blogpost");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost?cursor={cursor}&limit=2"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let second_page = test::read_body_json::<FeedDTO, _>(resp).await;
+        assert_eq!(second_page.blogposts.len(), 1);
+        assert_eq!(second_page.blogposts[0].excerpt.0, "Post 0");
+        assert!(second_page.next_cursor.is_none());
+        assert!(second_page.page.is_none());
+        assert_eq!(second_page.total_count, 4);
+        assert!(!second_page.has_next);
+        assert!(second_page.has_previous);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_feed_params() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
             .service(get_feed)
         ).await;
 
+        let uris = [
+            "/api/v1/blogpost?page=1&limit=0",
+            "/api/v1/blogpost?page=1&limit=51",
+            "/api/v1/blogpost?cursor=not_a_cursor",
+            "/api/v1/blogpost?page=1&cursor=MjAyNC0wMS0wMXwx",
+        ];
+        for uri in uris {
+            let req = test::TestRequest::get()
+                .uri(uri)
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{uri}");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_create_blogpost_unauthenticated() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+        ).await;
+
         let dto = CreateBlogPostDTO {
             text: "Hello!".to_string(),
-            username: "admin".to_string(),
-            avatar: Some("https://img.freepik.com/premium-psd/color-wing-png-isolated-transparent-background_1034016-9965.jpg".to_string()),
+            title: None,
+            tags: Vec::new(),
+            status: None,
+            publish_at: None,
         };
         let dto_str = to_string(&dto).expect("turning dto to json string");
 
-        let mut image_file = File::open(format!("images/placeholder_avatar"))
+        let mut image_file = File::open("images/placeholder_avatar")
             .expect("opening placeholder avatar");
 
         let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
             .await.unwrap();
 
         let req = test::TestRequest::post()
+            .set_payload(form.clone())
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .uri("/api/v1/blogpost")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        let req = test::TestRequest::post()
+            .insert_header(("Authorization", "Bearer not_a_token"))
             .set_payload(form)
             .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
             .uri("/api/v1/blogpost")
             .to_request();
 
         let resp = test::call_service(&app, req).await;
-        assert!(!resp.status().is_success());
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_modify_blogpost_not_author() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (post_id, other_user) = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            let author = create_user_session(&mut conn)?;
+            let other_user = create_user_session(&mut conn)?;
+            let dto = CreateBlogPostDTO {
+                text: "Hello!".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &author, None)?;
+            blogpost_service::get_blogposts(&mut conn, FeedPosition::Page(1), &FeedFilter::default(), DEFAULT_PAGE_SIZE)
+                .map(|(posts, _)| (posts[0].id, other_user))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a blogpost");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(update_blogpost)
+            .service(delete_blogpost)
+        ).await;
+
+        let dto = UpdateBlogPostDTO {
+            text: Some("Hello again!".to_string()),
+            title: None,
+            status: None,
+            publish_at: None,
+        };
+        let dto_str = to_string(&dto).expect("turning dto to json string");
+
+        let mut image_file = File::open("images/placeholder_avatar")
+            .expect("opening placeholder avatar");
+
+        let form = web::block(move || {create_multipart(dto_str, &mut image_file)})
+            .await.unwrap();
+
+        let req = test::TestRequest::patch()
+            .insert_header(("Authorization", format!("Bearer {}", other_user.token)))
+            .set_payload(form)
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let req = test::TestRequest::delete()
+            .insert_header(("Authorization", format!("Bearer {}", other_user.token)))
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn test_author_feed() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let author = web::block(move || {
+            let author = create_user_session(&mut conn)?;
+            let other_user = create_user_session(&mut conn)?;
+            for (i, user) in [&author, &other_user, &author, &author].into_iter().enumerate() {
+                let dto = CreateBlogPostDTO {
+                    text: format!("Post {i}"),
+                    title: None,
+                    tags: Vec::new(),
+                    status: None,
+                    publish_at: None,
+                };
+                blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), user, None)?;
+            }
+
+            // legacy post written under the same username before accounts existed
+            let dto = CreateBlogPostDTO {
+                text: "Legacy post".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            let legacy = blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &author, None)?;
+            diesel::update(BlogPostTable::table.find(legacy.id))
+                .set(BlogPostTable::user_id.eq(None::<i32>))
+                .execute(&mut conn)?;
+
+            Ok::<_, anyhow::Error>(author)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating blogposts");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(get_author_feed)
+        ).await;
 
-        // check if the created post is present
         let req = test::TestRequest::get()
-            .uri("/api/v1/blogpost?page=1")
+            .uri(&format!("/api/v1/users/{}/blogposts?page=1&limit=2", author.username))
             .to_request();
 
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_success());
+        let first_page: FeedDTO = test::read_body_json(resp).await;
+        // the legacy post is not the author's even though the username matches
+        assert_eq!(first_page.total_count, 3);
+        let texts: Vec<&str> = first_page.blogposts.iter().map(|post| post.excerpt.0.as_str()).collect();
+        assert_eq!(texts, ["Post 3", "Post 2"]);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/users/{}/blogposts?limit=2&cursor={}",
+                author.username, first_page.next_cursor.expect("next cursor is present")))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let second_page: FeedDTO = test::read_body_json(resp).await;
+        let texts: Vec<&str> = second_page.blogposts.iter().map(|post| post.excerpt.0.as_str()).collect();
+        assert_eq!(texts, ["Post 0"]);
+        assert!(!second_page.has_next);
+
+        // authors without posts have an empty feed
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/users/user-{}/blogposts?page=1", Uuid::new_v4()))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let feed: FeedDTO = test::read_body_json(resp).await;
+        assert_eq!(feed.total_count, 0);
+        assert!(feed.blogposts.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_markdown_rendering() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let text = "Some *emphasis* and [a link](https://example.com)\n\
+            <script>alert(1)</script>\n\n\
+            [bad](javascript:alert(1)) <img src=\"x\" onerror=\"alert(1)\">\n\n\
+            ```rust\nlet x = 1 < 2;\n```\n";
+        let mut conn = connection_pool.get().expect("getting connection");
+        let post_id = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            let user = create_user_session(&mut conn)?;
+            let dto = CreateBlogPostDTO {
+                text: text.to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &user, None)?;
+            blogpost_service::get_blogposts(&mut conn, FeedPosition::Page(1), &FeedFilter::default(), DEFAULT_PAGE_SIZE)
+                .map(|(posts, _)| posts[0].id)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a blogpost");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(get_blogpost)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+
+        // source is kept as it was written
+        assert_eq!(post.text, text);
+        let html = post.html.0;
+        assert!(html.contains("<em>emphasis</em>"));
+        assert!(html.contains("<a href=\"https://example.com\" rel=\"noopener noreferrer nofollow\">a link</a>"));
+        assert!(html.contains("<pre><code>let x = 1 &lt; 2;\n</code></pre>"));
+        assert!(!html.contains("script"));
+        assert!(!html.contains("javascript"));
+        assert!(!html.contains("onerror"));
+    }
+
+    #[actix_web::test]
+    async fn test_blogpost_slugs() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            create_user_session(&mut conn)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(get_blogpost_by_slug)
+            .service(update_blogpost)
+        ).await;
+
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let create = |title: Option<String>, text: String| {
+            let dto = CreateBlogPostDTO { text, title, tags: Vec::new(), status: None, publish_at: None };
+            test::TestRequest::post()
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(create_data_multipart(to_string(&dto).expect("turning dto to json string")))
+                .uri("/api/v1/blogpost")
+                .to_request()
+        };
+
+        // long texts are accepted and previewed with an excerpt
+        let title = format!("  Long Read:  Café {suffix} ");
+        let resp = test::call_service(&app, create(Some(title.clone()), "word ".repeat(12_000))).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let first: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(first.title, Some(format!("Long Read: Café {suffix}")));
+        assert_eq!(first.slug, Some(format!("long-read-cafe-{suffix}")));
+        assert!(first.excerpt.0.chars().count() <= EXCERPT_SIZE);
+        assert!(first.excerpt.0.ends_with("word\u{2026}"));
+
+        // the same title gets a numbered slug
+        let resp = test::call_service(&app, create(Some(title), "Hello!".to_string())).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let second: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(second.slug, Some(format!("long-read-cafe-{suffix}-2")));
+        assert_eq!(second.excerpt.0, "Hello!");
+
+        let resp = test::call_service(&app, create(None, "a".repeat(MAX_TEXT_SIZE + 1))).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/by-slug/long-read-cafe-{suffix}-2"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(post.id, second.id);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/by-slug/missing-{suffix}"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // posts get a slug with their first title and keep it afterwards
+        let resp = test::call_service(&app, create(None, "Hello!".to_string())).await;
+        let untitled: BlogPost = test::read_body_json(resp).await;
+        assert!(untitled.slug.is_none());
+
+        let update = |title: &str| {
+            let dto = UpdateBlogPostDTO { text: None, title: Some(title.to_string()), status: None, publish_at: None };
+            test::TestRequest::put()
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(create_data_multipart(to_string(&dto).expect("turning dto to json string")))
+                .uri(&format!("/api/v1/blogpost/{}", untitled.id))
+                .to_request()
+        };
+
+        let resp = test::call_service(&app, update(&format!("First {suffix}"))).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(post.slug, Some(format!("first-{suffix}")));
+
+        let resp = test::call_service(&app, update(&format!("Second {suffix}"))).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(post.title, Some(format!("Second {suffix}")));
+        assert_eq!(post.slug, Some(format!("first-{suffix}")));
+    }
+
+    #[actix_web::test]
+    async fn test_blogpost_status() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (author, other_user) = web::block(move || {
+            delete_all_posts(&mut conn)?;
+            Ok::<_, anyhow::Error>((create_user_session(&mut conn)?, create_user_session(&mut conn)?))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating user sessions");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(get_feed)
+            .service(get_own_blogposts)
+            .service(get_blogpost)
+            .service(update_blogpost)
+        ).await;
+
+        let create = |text: &str, status: Option<&str>, publish_at: Option<&str>| {
+            let dto = CreateBlogPostDTO {
+                text: text.to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: status.map(str::to_string),
+                publish_at: publish_at.map(|time| DateTime::parse_from_rfc3339(time).unwrap().to_utc()),
+            };
+            test::TestRequest::post()
+                .insert_header(("Authorization", format!("Bearer {}", author.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(create_data_multipart(to_string(&dto).expect("turning dto to json string")))
+                .uri("/api/v1/blogpost")
+                .to_request()
+        };
+
+        let resp = test::call_service(&app, create("Draft", Some("draft"), None)).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let draft: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(draft.status, "draft");
+        let resp = test::call_service(&app, create("Due", Some("scheduled"), Some("2024-01-01T12:00:00Z"))).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let due: BlogPost = test::read_body_json(resp).await;
+        let resp = test::call_service(&app, create("Later", Some("scheduled"), Some("2999-01-01T12:00:00Z"))).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let resp = test::call_service(&app, create("Published", None, None)).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        // only scheduled posts have a publish_at time
+        let resp = test::call_service(&app, create("Invalid", Some("scheduled"), None)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp = test::call_service(&app, create("Invalid", Some("draft"), Some("2024-01-01T12:00:00Z"))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp = test::call_service(&app, create("Invalid", Some("archived"), None)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let feed_texts = |feed: &FeedDTO| feed.blogposts.iter().map(|post| post.excerpt.0.clone()).collect::<Vec<_>>();
+        let req = test::TestRequest::get().uri("/api/v1/blogpost?page=1").to_request();
+        let feed: FeedDTO = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(feed_texts(&feed), ["Published"]);
+
+        // drafts are visible only to their author
+        let get_draft = |token: Option<&str>| {
+            let mut req = test::TestRequest::get().uri(&format!("/api/v1/blogpost/{}", draft.id));
+            if let Some(token) = token { req = req.insert_header(("Authorization", format!("Bearer {token}"))); }
+            req.to_request()
+        };
+        let resp = test::call_service(&app, get_draft(Some(&author.token))).await;
+        assert!(resp.status().is_success());
+        let resp = test::call_service(&app, get_draft(Some(&other_user.token))).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let resp = test::call_service(&app, get_draft(None)).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::get()
+            .insert_header(("Authorization", format!("Bearer {}", author.token)))
+            .uri("/api/v1/users/me/blogposts?page=1&status=draft")
+            .to_request();
+        let feed: FeedDTO = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(feed_texts(&feed), ["Draft"]);
+
+        let req = test::TestRequest::get()
+            .insert_header(("Authorization", format!("Bearer {}", author.token)))
+            .uri("/api/v1/users/me/blogposts?page=1")
+            .to_request();
+        let feed: FeedDTO = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(feed.total_count, 4);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/users/me/blogposts?page=1")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // scheduled posts are published at their publish_at time once it passes
+        let mut conn = connection_pool.get().expect("getting connection");
+        let published = web::block(move || blogpost_service::publish_scheduled(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("publishing scheduled blogposts");
+        assert_eq!(published, 1);
+
+        let req = test::TestRequest::get().uri(&format!("/api/v1/blogpost/{}", due.id)).to_request();
+        let post: BlogPost = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(post.status, "published");
+        assert_eq!(post.published_at, DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z").unwrap().to_utc());
+
+        // publishing a draft moves it to the top of the feed
+        let dto = UpdateBlogPostDTO { status: Some("published".to_string()), ..Default::default() };
+        let req = test::TestRequest::put()
+            .insert_header(("Authorization", format!("Bearer {}", author.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_data_multipart(to_string(&dto).expect("turning dto to json string")))
+            .uri(&format!("/api/v1/blogpost/{}", draft.id))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
 
-        let body_bytes = resp
-            .into_body()
-            .try_into_bytes()
-            .expect("reading response bytes")
-            .to_vec();
-        let body = String::from_utf8(body_bytes).expect("reading response bytes as string");
-        let unescaped_body = from_str::<String>(&body).expect("reading unsecaped body");
-        let feed: FeedDTO = from_str(&unescaped_body).expect("parsing feed body");
-        assert!(feed.blogposts.len() == 0);
+        let req = test::TestRequest::get().uri("/api/v1/blogpost?page=1").to_request();
+        let feed: FeedDTO = test::read_body_json(test::call_service(&app, req).await).await;
+        assert_eq!(feed_texts(&feed), ["Draft", "Published", "Due"]);
     }
 }
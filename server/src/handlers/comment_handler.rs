@@ -0,0 +1,149 @@
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use log::{log, Level};
+use crate::auth::AuthenticatedUser;
+use crate::config::Config;
+use crate::db::DBPool;
+use crate::models::{
+    CommentsDTO, CommentsQuery, CreateCommentDTO, GenericErrorMessageDTO,
+    DEFAULT_COMMENTS_PAGE_SIZE, MAX_PAGE_SIZE
+};
+use crate::service::blogpost_service::AuthorOnly;
+use crate::service::comment_service::{self, NewCommentOutcome};
+
+/// adds a comment authored by the authenticated user to the blogpost,
+/// the comment is a reply if `parent_id` is provided
+#[post("/api/v1/blogpost/{id}/comments")]
+async fn create_comment(
+    user: AuthenticatedUser,
+    post_id: web::Path<i32>,
+    dto: web::Json<CreateCommentDTO>,
+    pool: web::Data<DBPool>,
+    config: web::Data<Config>) -> impl Responder {
+    let post_id = post_id.into_inner();
+    let dto = dto.into_inner();
+    if !dto.is_valid() {
+        let err_dto = GenericErrorMessageDTO::new("Comment must be between 1 and 1000 characters!".to_string());
+        return HttpResponse::BadRequest().json(err_dto);
+    }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let max_depth = config.max_comment_depth;
+    let res = web::block(move || comment_service::create_comment(&mut conn, post_id, dto, &user, max_depth)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error saving a comment on blogpost {}: {}", post_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error saving a comment on blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        NewCommentOutcome::Created(comment) => HttpResponse::Created().json(comment),
+        NewCommentOutcome::PostNotFound => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {post_id} does not exist!"));
+            HttpResponse::NotFound().json(err_dto)
+        }
+        NewCommentOutcome::ParentNotFound => {
+            let err_dto = GenericErrorMessageDTO::new("Comment that is replied to does not exist!".to_string());
+            HttpResponse::NotFound().json(err_dto)
+        }
+        NewCommentOutcome::TooDeep => {
+            let err_dto = GenericErrorMessageDTO::new(
+                format!("Replies cannot be nested deeper than {max_depth} levels!"));
+            HttpResponse::BadRequest().json(err_dto)
+        }
+    }
+}
+
+/// returns a page of top level comments on the blogpost, from the oldest to the newest,
+/// each followed by its replies
+/// comments on drafts and scheduled posts are returned only to the author of the post
+#[get("/api/v1/blogpost/{id}/comments")]
+async fn get_comments(
+    user: Option<AuthenticatedUser>,
+    req: HttpRequest,
+    post_id: web::Path<i32>,
+    pool: web::Data<DBPool>) -> impl Responder {
+    let post_id = post_id.into_inner();
+    let viewer_id = user.map(|user| user.id);
+
+    let params = web::Query::<CommentsQuery>::from_query(req.query_string());
+    if params.is_err() { return HttpResponse::BadRequest().finish(); }
+    let params = params.unwrap().into_inner();
+
+    let page = params.page.unwrap_or(1);
+    if page < 1 { return HttpResponse::BadRequest().finish(); }
+    let limit = params.limit.unwrap_or(DEFAULT_COMMENTS_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) { return HttpResponse::BadRequest().finish(); }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || comment_service::get_comments(&mut conn, post_id, viewer_id, page, limit)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting comments on blogpost {}: {}", post_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting comments on blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        Some((comments, total_count)) => HttpResponse::Ok().json(CommentsDTO::new(comments, page, limit, total_count)),
+        None => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {post_id} does not exist!"));
+            HttpResponse::NotFound().json(err_dto)
+        }
+    }
+}
+
+/// deletes a comment, only the author can delete the comment
+/// comments with replies are kept as tombstones without the text
+#[delete("/api/v1/blogpost/{id}/comments/{comment_id}")]
+async fn delete_comment(user: AuthenticatedUser, path: web::Path<(i32, i32)>, pool: web::Data<DBPool>) -> impl Responder {
+    let (post_id, comment_id) = path.into_inner();
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || comment_service::delete_comment(&mut conn, post_id, comment_id, user.id)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error deleting comment {}: {}", comment_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error deleting comment {}: {}", comment_id, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        AuthorOnly::Done(()) => HttpResponse::NoContent().finish(),
+        AuthorOnly::NotFound => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Comment {comment_id} does not exist!"));
+            HttpResponse::NotFound().json(err_dto)
+        }
+        AuthorOnly::NotAuthor => {
+            let err_dto = GenericErrorMessageDTO::new("Only the author can delete the comment!".to_string());
+            HttpResponse::Forbidden().json(err_dto)
+        }
+    }
+}
@@ -0,0 +1,393 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web::{self, Data}, App};
+    use crate::{
+        auth::authenticate,
+        config::Config,
+        db::establish_connection_pool,
+        handlers::{
+            blogpost_handler::get_blogpost,
+            comment_handler::{create_comment, delete_comment, get_comments},
+            test_utils::{create_user_session, create_user_with_draft, create_user_with_post}},
+        models::{BlogPost, Comment, CommentsDTO, CreateCommentDTO}};
+
+    // TESTS NEED TO BE RAN SEQUENTIALLY
+
+    #[actix_web::test]
+    async fn test_create_and_get_comments() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (user, post_id) = web::block(move || create_user_with_post(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user with a post");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_comment)
+            .service(get_comments)
+            .service(get_blogpost)
+        ).await;
+
+        for i in 0..3 {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .set_json(CreateCommentDTO { text: format!("Comment {i}"), parent_id: None })
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+            let comment: Comment = test::read_body_json(resp).await;
+            assert_eq!(comment.post_id, post_id);
+            assert_eq!(comment.username.as_deref(), Some(user.username.as_str()));
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments?page=1&limit=2"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        assert_eq!(page.total_count, 3);
+        assert!(page.has_next);
+        assert!(!page.has_previous);
+        let texts: Vec<&str> = page.comments.iter().map(|comment| comment.text.as_str()).collect();
+        assert_eq!(texts, ["Comment 0", "Comment 1"]);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments?page=2&limit=2"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        assert!(!page.has_next);
+        assert!(page.has_previous);
+        assert_eq!(page.comments.len(), 1);
+        assert_eq!(page.comments[0].text, "Comment 2");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(post.comment_count, 3);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_comments() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (user, post_id) = web::block(move || create_user_with_post(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user with a post");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_comment)
+            .service(get_comments)
+        ).await;
+
+        for text in ["".to_string(), " \n\t".to_string(), "a".repeat(1001)] {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .set_json(CreateCommentDTO { text, parent_id: None })
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        // the length is counted in characters of the trimmed text
+        let text = "é".repeat(1000);
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_json(CreateCommentDTO { text: format!("  {text}\n"), parent_id: None })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let comment: Comment = test::read_body_json(resp).await;
+        assert_eq!(comment.text, text);
+
+        // missing authorization
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .set_json(CreateCommentDTO { text: "Comment".to_string(), parent_id: None })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // nonexistent blogpost
+        let req = test::TestRequest::post()
+            .uri("/api/v1/blogpost/-1/comments")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_json(CreateCommentDTO { text: "Comment".to_string(), parent_id: None })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/blogpost/-1/comments")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        for query in ["page=0", "limit=0", "limit=51", "page=a"] {
+            let req = test::TestRequest::get()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments?{query}"))
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        // only the comment with the trimmed text was saved
+        assert_eq!(page.total_count, 1);
+    }
+
+    #[actix_web::test]
+    async fn test_comment_replies() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let ((user, post_id), (other_user, _)) = web::block(move || -> anyhow::Result<_> {
+            Ok((create_user_with_post(&mut conn)?, create_user_with_post(&mut conn)?))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating users with posts");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config { max_comment_depth: 2, ..Config::default() }))
+            .service(create_comment)
+            .service(get_comments)
+            .service(delete_comment)
+            .service(get_blogpost)
+        ).await;
+
+        // "a" <- "b" <- "c" <- "d" is too deep, "f" replies to "a" after "e" was posted
+        let mut ids: Vec<i32> = Vec::new();
+        for (text, parent) in [("a", None), ("b", Some(0)), ("c", Some(1)), ("e", None), ("f", Some(0))] {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .set_json(CreateCommentDTO { text: text.to_string(), parent_id: parent.map(|i: usize| ids[i]) })
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+            let comment: Comment = test::read_body_json(resp).await;
+            ids.push(comment.id);
+        }
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_json(CreateCommentDTO { text: "d".to_string(), parent_id: Some(ids[2]) })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        assert_eq!(page.total_count, 2);
+        let thread: Vec<(&str, i32)> = page.comments.iter().map(|comment| (comment.text.as_str(), comment.depth)).collect();
+        assert_eq!(thread, [("a", 0), ("b", 1), ("c", 2), ("f", 1), ("e", 0)]);
+        assert_eq!(page.comments[2].parent_id, Some(ids[1]));
+
+        // only the author can delete the comment
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments/{}", ids[1]))
+            .insert_header(("Authorization", format!("Bearer {}", other_user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        // "b" has a reply so it becomes a tombstone, "e" is removed
+        for id in [ids[1], ids[3]] {
+            let req = test::TestRequest::delete()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments/{id}"))
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        let thread: Vec<&str> = page.comments.iter().map(|comment| comment.text.as_str()).collect();
+        assert_eq!(thread, ["a", "", "c", "f"]);
+        assert!(page.comments[1].deleted_at.is_some());
+        // tombstones do not reveal their author
+        assert_eq!(page.comments[1].user_id, None);
+        assert_eq!(page.comments[1].username, None);
+        assert!(page.comments[0].user_id.is_some());
+
+        // tombstones cannot be replied to or deleted again
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .set_json(CreateCommentDTO { text: "g".to_string(), parent_id: Some(ids[1]) })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments/{}", ids[1]))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(post.comment_count, 3);
+
+        // deleting the last reply of "b" removes the tombstone as well
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments/{}", ids[2]))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        let thread: Vec<&str> = page.comments.iter().map(|comment| comment.text.as_str()).collect();
+        assert_eq!(thread, ["a", "f"]);
+        assert!(page.comments.iter().all(|comment| comment.id != ids[1]));
+    }
+
+    #[actix_web::test]
+    async fn test_draft_comments() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let ((author, post_id), other_user) = web::block(move || -> anyhow::Result<_> {
+            Ok((create_user_with_draft(&mut conn)?, create_user_session(&mut conn)?))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating users with a draft");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_comment)
+            .service(get_comments)
+        ).await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .insert_header(("Authorization", format!("Bearer {}", author.token)))
+            .set_json(CreateCommentDTO { text: "Note to self".to_string(), parent_id: None })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let comment: Comment = test::read_body_json(resp).await;
+
+        // drafts do not exist for anyone but their author
+        for parent_id in [None, Some(comment.id)] {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+                .insert_header(("Authorization", format!("Bearer {}", other_user.token)))
+                .set_json(CreateCommentDTO { text: "Hi!".to_string(), parent_id })
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        }
+
+        for token in [None, Some(&other_user.token)] {
+            let mut req = test::TestRequest::get()
+                .uri(&format!("/api/v1/blogpost/{post_id}/comments"));
+            if let Some(token) = token {
+                req = req.insert_header(("Authorization", format!("Bearer {token}")));
+            }
+
+            let resp = test::call_service(&app, req.to_request()).await;
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}/comments"))
+            .insert_header(("Authorization", format!("Bearer {}", author.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let page: CommentsDTO = test::read_body_json(resp).await;
+        assert_eq!(page.total_count, 1);
+    }
+}
@@ -1,25 +1,147 @@
-use actix_web::{get, web, HttpResponse, Responder};
+use actix_web::{body::SizedStream, get, web, HttpRequest, HttpResponse, Responder};
+use actix_web::http::header::{
+    ByteRangeSpec, CacheControl, CacheDirective, ContentRange, ContentRangeSpec, ETag, EntityTag,
+    Header, IfNoneMatch, IfRange, Range, ACCEPT_RANGES, IF_RANGE
+};
 use uuid::Uuid;
 use log::{log, Level};
 
+use crate::models::{ImageQuery, ImageVariant, IMAGE_VARIANTS};
 use crate::service::image_service;
 
+/// images are stored under a new uuid every time, so a cached image never goes stale
+const IMAGE_MAX_AGE: u32 = 365 * 24 * 60 * 60;
 
-/// serves the image based on the provided uuid in the path
+/// part of the image asked for by the Range header
+enum RequestedRange {
+    Whole,
+    /// first and last byte
+    Part(u64, u64),
+    Unsatisfiable,
+}
+
+/// reads the Range and If-Range headers, the whole image is sent when the Range header is missing or invalid,
+/// has more than one range, or If-Range does not match the current ETag
+fn requested_range(req: &HttpRequest, etag: &EntityTag, size: u64) -> RequestedRange {
+    let Ok(Range::Bytes(ranges)) = Range::parse(req) else { return RequestedRange::Whole };
+    let [range]: [ByteRangeSpec; 1] = match ranges.try_into() {
+        Ok(range) => range,
+        Err(_) => return RequestedRange::Whole,
+    };
+
+    // without Last-Modified only a strong ETag can validate If-Range
+    if req.headers().contains_key(IF_RANGE) {
+        match IfRange::parse(req) {
+            Ok(IfRange::EntityTag(tag)) if tag.strong_eq(etag) => {}
+            _ => return RequestedRange::Whole,
+        }
+    }
+
+    match range.to_satisfiable_range(size) {
+        Some((start, end)) => RequestedRange::Part(start, end),
+        None => RequestedRange::Unsatisfiable,
+    }
+}
+
+/// checks the uuid from the path, the placeholder avatar is accepted as well
+/// returns the file name with the Cache-Control header the image is served with, None if the uuid is invalid
+fn image_filename(uuid: String) -> Option<(String, CacheControl)> {
+    // placeholder avatar can change between releases, browsers revalidate it with the ETag
+    if uuid == image_service::PLACEHOLDER_AVATAR {
+        return Some((uuid, CacheControl(vec![CacheDirective::Public, CacheDirective::NoCache])));
+    }
+
+    let uuid = Uuid::try_parse(&uuid).ok()?;
+    Some((uuid.to_string(), CacheControl(vec![
+        CacheDirective::Public,
+        CacheDirective::MaxAge(IMAGE_MAX_AGE),
+        CacheDirective::Extension("immutable".to_string(), None),
+    ])))
+}
+
+/// serves the image based on the provided uuid in the path, with `w` the narrowest variant at least that wide
+///
+/// responds 304 if the ETag from If-None-Match is still current,
+/// a single byte range is served with 206 and 416 is returned when the range is outside of the image
 #[get("/api/v1/image/{uuid}")]
-pub async fn get_image(uuid: web::Path<String>) -> impl Responder {
-    let uuid = uuid.into_inner();
-
-    let filename;
-    if uuid == "placeholder_avatar" { filename = uuid }
-    else {
-        let uuid = Uuid::try_parse(&uuid);
-        if let Err(_) = uuid { return HttpResponse::BadRequest().finish(); }
-        let uuid = uuid.unwrap();
-        filename = uuid.to_string();
+pub async fn get_image(req: HttpRequest, uuid: web::Path<String>) -> impl Responder {
+    let params = web::Query::<ImageQuery>::from_query(req.query_string());
+    if params.is_err() { return HttpResponse::BadRequest().finish(); }
+    let params = params.unwrap().into_inner();
+
+    let variants = match params.w {
+        Some(width) => IMAGE_VARIANTS.into_iter().filter(|variant| variant.width >= width).collect(),
+        None => Vec::new(),
+    };
+    image_response(&req, uuid.into_inner(), variants).await
+}
+
+/// serves the variant of the image, the original image if it is narrower than the variant
+#[get("/api/v1/image/{uuid}/{variant}")]
+pub async fn get_image_variant(req: HttpRequest, path: web::Path<(String, String)>) -> impl Responder {
+    let (uuid, variant) = path.into_inner();
+    let variant = ImageVariant::from_name(&variant);
+    if variant.is_none() { return HttpResponse::BadRequest().finish(); }
+
+    image_response(&req, uuid, vec![variant.unwrap()]).await
+}
+
+/// serves the first of the variants the image has, the original image if it has none of them
+async fn image_response(req: &HttpRequest, uuid: String, variants: Vec<ImageVariant>) -> HttpResponse {
+    let filename = image_filename(uuid);
+    if filename.is_none() { return HttpResponse::BadRequest().finish(); }
+    let (filename, cache_control) = filename.unwrap();
+
+    let meta = web::block(move || -> anyhow::Result<_> {
+        let mut candidates: Vec<String> = variants.into_iter()
+            .map(|variant| image_service::variant_id(&filename, variant))
+            .collect();
+        candidates.push(filename);
+        for filename in candidates {
+            let meta = image_service::image_meta(&filename)?;
+            let size = image_service::image_size(&filename)?;
+            if let Some((meta, size)) = meta.zip(size) { return Ok(Some((filename, meta, size))); }
+        }
+        Ok(None)
+    }).await;
+    if let Err(e) = meta {
+        log!(Level::Error, "Error reading image metadata: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let meta = meta.unwrap();
+    if let Err(e) = meta {
+        log!(Level::Error, "Error reading image metadata: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+    let meta = meta.unwrap();
+    if meta.is_none() { return HttpResponse::BadRequest().finish(); }
+    let (filename, meta, size) = meta.unwrap();
+
+    let etag = EntityTag::new_strong(meta.etag);
+    let not_modified = match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        Err(_) => false,
+    };
+    if not_modified {
+        return HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .insert_header(cache_control)
+            .finish();
     }
 
-    let image_res = image_service::get_image(filename)
+    let range = match requested_range(req, &etag, size) {
+        RequestedRange::Whole => None,
+        RequestedRange::Part(start, end) => Some((start, end)),
+        RequestedRange::Unsatisfiable => {
+            return HttpResponse::RangeNotSatisfiable()
+                .insert_header((ACCEPT_RANGES, "bytes"))
+                .insert_header(ContentRange(ContentRangeSpec::Bytes { range: None, instance_length: Some(size) }))
+                .finish();
+        }
+    };
+
+    let image_res = image_service::get_image(filename, range)
         .await;
 
     if let Err(e) = image_res {
@@ -31,7 +153,23 @@ pub async fn get_image(uuid: web::Path<String>) -> impl Responder {
     if stream.is_none() { return HttpResponse::BadRequest().finish(); }
     let stream = stream.unwrap(); // safe to unwrap because its always present
 
-    HttpResponse::Ok()
-        .content_type("application/octet-stream")
-        .streaming(stream)
+    let mut response = match range {
+        Some((start, end)) => {
+            let mut response = HttpResponse::PartialContent();
+            response.insert_header(ContentRange(ContentRangeSpec::Bytes {
+                range: Some((start, end)),
+                instance_length: Some(size),
+            }));
+            response
+        }
+        None => HttpResponse::Ok(),
+    };
+    let length = range.map_or(size, |(start, end)| end - start + 1);
+
+    response
+        .content_type(meta.mime_type)
+        .insert_header(ETag(etag))
+        .insert_header(cache_control)
+        .insert_header((ACCEPT_RANGES, "bytes"))
+        .body(SizedStream::new(length, stream))
 }
@@ -1,11 +1,125 @@
 #[cfg(test)]
 mod tests {
-    use std::env;
+    use std::{env, io::{Cursor, Write}, path::Path, time::Duration};
 
-    use actix_web::{test, web::Data, App};
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web::{self, Data}, App};
+    use image::{ImageFormat, RgbImage};
+    use serde_json::to_string;
     use tokio::fs;
     use uuid::Uuid;
-    use crate::{db::establish_connection_pool, handlers::image_handler::get_image};
+    use crate::{
+        auth::authenticate,
+        config::Config,
+        db::establish_connection_pool,
+        handlers::{
+            blogpost_handler::{create_blogpost, delete_blogpost},
+            image_handler::{get_image, get_image_variant},
+            test_utils::create_user_session,
+            user_handler::set_avatar},
+        models::{BlogPost, CreateBlogPostDTO, ProfileDTO},
+        service::image_service};
+
+    /// helper function to manually construct a multipart form payload, the data field is left out when it is None
+    fn create_multipart(dto: Option<String>, image_field: &str, image: &[u8]) -> Vec<u8> {
+        let boundary = "my_boundary";
+        let mut body = Vec::new();
+
+        if let Some(dto) = dto {
+            write!(
+                &mut body,
+                "--{}\r\n\
+                Content-Disposition: form-data; name=\"data\"\r\n\r\n\
+            {}\r\n",
+            boundary, dto).unwrap();
+        }
+
+        write!(
+            &mut body,
+            "--{}\r\n\
+            Content-Disposition: form-data; name=\"{}\"; filename=\"image\"\r\n\
+        Content-Type: application/octet-stream\r\n\r\n",
+        boundary, image_field).unwrap();
+        body.extend(image);
+        body.write_all(b"\r\n").unwrap();
+
+        write!(
+            &mut body,
+            "--{}--\r\n",
+            boundary).unwrap();
+
+        body
+    }
+
+    /// helper function that encodes a gradient image of the given size
+    fn encode_image(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, y| image::Rgb([(x % 256) as u8, (y % 256) as u8, 128]));
+        let mut encoded = Vec::new();
+        image.write_to(&mut Cursor::new(&mut encoded), format).expect("encoding an image");
+        encoded
+    }
+
+    /// GPS latitude stored in the EXIF of the test photos, 29.46 seconds as a big endian rational
+    const LATITUDE_SECONDS: [u8; 8] = [0x00, 0x00, 0x0B, 0x82, 0x00, 0x00, 0x00, 0x64];
+
+    /// helper function that builds a big endian TIFF structure as stored in the EXIF of a photo,
+    /// with the given orientation and a GPS latitude of 48° 51' 29.46" N
+    fn exif_with_location(orientation: u16) -> Vec<u8> {
+        let mut tiff = b"MM\x00\x2A\x00\x00\x00\x08".to_vec();
+        // IFD0 with the orientation and the offset of the GPS IFD
+        tiff.extend([0x00, 0x02]);
+        tiff.extend([0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]);
+        tiff.extend(orientation.to_be_bytes());
+        tiff.extend([0x00, 0x00]);
+        tiff.extend([0x88, 0x25, 0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x26]);
+        tiff.extend([0x00, 0x00, 0x00, 0x00]);
+        // GPS IFD with the latitude reference and the offset of the latitude
+        tiff.extend([0x00, 0x02]);
+        tiff.extend([0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, b'N', 0x00, 0x00, 0x00]);
+        tiff.extend([0x00, 0x02, 0x00, 0x05, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x44]);
+        tiff.extend([0x00, 0x00, 0x00, 0x00]);
+        tiff.extend([0x00, 0x00, 0x00, 0x30, 0x00, 0x00, 0x00, 0x01]);
+        tiff.extend([0x00, 0x00, 0x00, 0x33, 0x00, 0x00, 0x00, 0x01]);
+        tiff.extend(LATITUDE_SECONDS);
+        tiff
+    }
+
+    /// helper function that encodes a JPEG photo with its left half red and its right half blue,
+    /// the EXIF says it has to be turned 90° clockwise and where it was taken
+    fn encode_rotated_photo(width: u32, height: u32) -> Vec<u8> {
+        let image = RgbImage::from_fn(width, height, |x, _| {
+            if x < width / 2 { image::Rgb([255, 0, 0]) } else { image::Rgb([0, 0, 255]) }
+        });
+        let mut encoded = Vec::new();
+        image.write_to(&mut Cursor::new(&mut encoded), ImageFormat::Jpeg).expect("encoding an image");
+
+        let mut app1 = b"Exif\x00\x00".to_vec();
+        app1.extend(exif_with_location(6));
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend((app1.len() as u16 + 2).to_be_bytes());
+        segment.extend(app1);
+        // right after the start of image marker
+        encoded.splice(2..2, segment);
+        encoded
+    }
+
+    /// helper function that encodes a lossless WebP image in an extended container with an EXIF chunk
+    fn encode_webp_with_location(width: u32, height: u32) -> Vec<u8> {
+        let lossless = encode_image(width, height, ImageFormat::WebP);
+        let exif = exif_with_location(1);
+
+        let mut webp = b"RIFF\x00\x00\x00\x00WEBPVP8X\x0A\x00\x00\x00".to_vec();
+        webp.extend([0x08, 0x00, 0x00, 0x00]);
+        webp.extend(&(width - 1).to_le_bytes()[..3]);
+        webp.extend(&(height - 1).to_le_bytes()[..3]);
+        webp.extend(&lossless[12..]);
+        webp.extend(b"EXIF");
+        webp.extend((exif.len() as u32).to_le_bytes());
+        webp.extend(&exif);
+        if exif.len() % 2 == 1 { webp.push(0); }
+        let riff_size = (webp.len() - 8) as u32;
+        webp[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        webp
+    }
 
     #[actix_web::test]
     async fn test_placeholder_avatar() {
@@ -73,5 +187,415 @@ mod tests {
 
         let resp = test::call_service(&app, req).await;
         assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/png");
+        assert_eq!(resp.headers().get("Cache-Control").unwrap(), "public, max-age=31536000, immutable");
+        let etag = resp.headers().get("ETag").expect("ETag is present").to_str().unwrap().to_string();
+        assert!(!etag.starts_with("W/"));
+
+        // the ETag is derived from the content, a metadata file left next to the placeholder is not trusted
+        fs::write("images/placeholder_avatar.meta", r#"{"mime_type":"image/png","etag":"stale"}"#)
+            .await
+            .expect("writing stale placeholder metadata");
+        let req = test::TestRequest::get()
+            .uri("/api/v1/image/placeholder_avatar")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("ETag").unwrap().to_str().unwrap(), etag);
+        assert_eq!(resp.headers().get("Cache-Control").unwrap(), "public, no-cache");
+        fs::remove_file("images/placeholder_avatar.meta").await.expect("removing stale placeholder metadata");
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/image/{uuid}"))
+            .insert_header(("If-None-Match", format!("\"other\", {etag}")))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(resp.headers().get("ETag").unwrap().to_str().unwrap(), etag);
+        assert!(test::read_body(resp).await.is_empty());
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/image/{uuid}"))
+            .insert_header(("If-None-Match", "\"other\""))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        fs::remove_file(format!("images/{uuid}")).await.expect("removing the dummy image");
+        fs::remove_file(format!("images/{uuid}.meta")).await.expect("removing the dummy image metadata");
+    }
+
+    #[actix_web::test]
+    async fn test_aborted_upload_removed() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+        ).await;
+
+        let count_images = || std::fs::read_dir("images").expect("reading the images directory").count();
+        let images_before = count_images();
+
+        // the upload stops in the middle of the image, without the closing boundary
+        let dto = CreateBlogPostDTO { text: "Photo".to_string(), title: None, tags: Vec::new(), status: None, publish_at: None };
+        let mut body = create_multipart(
+            Some(to_string(&dto).expect("turning dto to json string")),
+            "image",
+            &encode_image(400, 300, ImageFormat::Png));
+        body.truncate(body.len() - 100);
+        let req = test::TestRequest::post()
+            .uri("/api/v1/blogpost")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(body)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(!resp.status().is_success());
+        assert_eq!(count_images(), images_before);
+    }
+
+    #[actix_web::test]
+    async fn test_image_ranges() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(get_image)
+        ).await;
+
+        let uuid = Uuid::new_v4().to_string();
+        fs::copy("images/placeholder_avatar", format!("images/{uuid}"))
+            .await
+            .expect("making a dummy image");
+        let image = fs::read(format!("images/{uuid}")).await.expect("reading the dummy image");
+        let size = image.len();
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/image/{uuid}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Accept-Ranges").unwrap(), "bytes");
+        let etag = resp.headers().get("ETag").unwrap().to_str().unwrap().to_string();
+
+        let request = |range: &str, if_range: Option<&str>| {
+            let mut req = test::TestRequest::get()
+                .uri(&format!("/api/v1/image/{uuid}"))
+                .insert_header(("Range", range));
+            if let Some(if_range) = if_range { req = req.insert_header(("If-Range", if_range)); }
+            req.to_request()
+        };
+
+        let resp = test::call_service(&app, request("bytes=0-9", None)).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(resp.headers().get("Content-Range").unwrap().to_str().unwrap(), format!("bytes 0-9/{size}"));
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/png");
+        assert_eq!(test::read_body(resp).await, image[..10]);
+
+        // suffix ranges and ranges past the end are cut to the image
+        let resp = test::call_service(&app, request("bytes=-5", None)).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(test::read_body(resp).await, image[size - 5..]);
+
+        let resp = test::call_service(&app, request(&format!("bytes={}-{}", size - 3, size + 100), Some(&etag))).await;
+        assert_eq!(resp.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(test::read_body(resp).await, image[size - 3..]);
+
+        let resp = test::call_service(&app, request(&format!("bytes={size}-"), None)).await;
+        assert_eq!(resp.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(resp.headers().get("Content-Range").unwrap().to_str().unwrap(), format!("bytes */{size}"));
+
+        // the whole image is sent if it changed since the part was fetched, or more ranges are requested
+        for (range, if_range) in [("bytes=0-9", Some("\"other\"")), ("bytes=0-9,20-29", None), ("items=0-9", None)] {
+            let resp = test::call_service(&app, request(range, if_range)).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(test::read_body(resp).await, image);
+        }
+
+        fs::remove_file(format!("images/{uuid}")).await.expect("removing the dummy image");
+        fs::remove_file(format!("images/{uuid}.meta")).await.expect("removing the dummy image metadata");
+    }
+
+    #[actix_web::test]
+    async fn test_image_variants() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(delete_blogpost)
+            .service(set_avatar)
+            .service(get_image)
+            .service(get_image_variant)
+        ).await;
+
+        let dto = CreateBlogPostDTO { text: "Photo".to_string(), title: None, tags: Vec::new(), status: None, publish_at: None };
+        let req = test::TestRequest::post()
+            .uri("/api/v1/blogpost")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_multipart(
+                Some(to_string(&dto).expect("turning dto to json string")),
+                "image",
+                &encode_image(1200, 800, ImageFormat::Jpeg)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let post: BlogPost = test::read_body_json(resp).await;
+        let image_id = post.post_image.expect("post image is saved");
+
+        // the narrowest variant that is at least as wide as asked for, the original when none of them is
+        for (uri, width, height) in [
+            (format!("/api/v1/image/{image_id}/small"), 480, 320),
+            (format!("/api/v1/image/{image_id}/large"), 1080, 720),
+            (format!("/api/v1/image/{image_id}?w=40"), 480, 320),
+            (format!("/api/v1/image/{image_id}?w=481"), 1080, 720),
+            (format!("/api/v1/image/{image_id}?w=2000"), 1200, 800),
+            (format!("/api/v1/image/{image_id}/avatar"), 1200, 800),
+        ] {
+            let req = test::TestRequest::get().uri(&uri).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/jpeg");
+            let body = test::read_body(resp).await;
+            let variant = image::load_from_memory(&body).expect("decoding the variant");
+            assert_eq!((variant.width(), variant.height()), (width, height), "{uri}");
+        }
+
+        let req = test::TestRequest::get().uri(&format!("/api/v1/image/{image_id}/huge")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // avatars get a PNG variant unless they are JPEG
+        let req = test::TestRequest::put()
+            .uri("/api/v1/users/me/avatar")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_multipart(None, "image", &encode_image(200, 100, ImageFormat::Gif)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let profile: ProfileDTO = test::read_body_json(resp).await;
+        let avatar = profile.avatar.expect("avatar is set");
+
+        let req = test::TestRequest::get().uri(&format!("/api/v1/image/{avatar}/avatar")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/png");
+        let variant = image::load_from_memory(&test::read_body(resp).await).expect("decoding the variant");
+        assert_eq!((variant.width(), variant.height()), (64, 32));
+        assert!(Path::new(&format!("images/{avatar}.avatar")).exists());
+
+        // variants are deleted with the image
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{}", post.id))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        for filename in [&image_id, &format!("{image_id}.small"), &format!("{image_id}.large.meta")] {
+            assert!(!Path::new(&format!("images/{filename}")).exists());
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_marked_image_variants() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(get_image_variant)
+        ).await;
+
+        let dto = CreateBlogPostDTO { text: "Photo".to_string(), title: None, tags: Vec::new(), status: None, publish_at: None };
+        let req = test::TestRequest::post()
+            .uri("/api/v1/blogpost")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_multipart(
+                Some(to_string(&dto).expect("turning dto to json string")),
+                "image",
+                &encode_image(1200, 800, ImageFormat::Png)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let post: BlogPost = test::read_body_json(resp).await;
+        let image_id = post.post_image.expect("post image is saved");
+
+        // variants stop being served together with the marked image
+        let marked_id = image_service::mark_image_deleted(&image_id)
+            .expect("marking the image")
+            .expect("image exists");
+        let req = test::TestRequest::get().uri(&format!("/api/v1/image/{image_id}/small")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        image_service::restore_image(&marked_id).expect("restoring the image");
+        let req = test::TestRequest::get().uri(&format!("/api/v1/image/{image_id}/small")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // marked images that were not removed are swept later
+        image_service::mark_image_deleted(&image_id)
+            .expect("marking the image")
+            .expect("image exists");
+        let deleted = image_service::delete_marked_images(Duration::from_secs(60 * 60))
+            .await
+            .expect("sweeping marked images");
+        assert_eq!(deleted, 0);
+        assert!(Path::new(&format!("images/{marked_id}")).exists());
+
+        image_service::delete_marked_images(Duration::ZERO)
+            .await
+            .expect("sweeping marked images");
+        for filename in [&marked_id, &format!("{image_id}.small.deleted"), &format!("{image_id}.large.meta.deleted")] {
+            assert!(!Path::new(&format!("images/{filename}")).exists(), "{filename}");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_image_metadata_removed() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(delete_blogpost)
+            .service(set_avatar)
+            .service(get_image)
+            .service(get_image_variant)
+        ).await;
+
+        let photo = encode_rotated_photo(1200, 600);
+        assert!(photo.windows(LATITUDE_SECONDS.len()).any(|bytes| bytes == LATITUDE_SECONDS));
+
+        let dto = CreateBlogPostDTO { text: "Photo".to_string(), title: None, tags: Vec::new(), status: None, publish_at: None };
+        let req = test::TestRequest::post()
+            .uri("/api/v1/blogpost")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_multipart(Some(to_string(&dto).expect("turning dto to json string")), "image", &photo))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let post: BlogPost = test::read_body_json(resp).await;
+        let image_id = post.post_image.expect("post image is saved");
+
+        // the photo and its variant are turned upright, the red half ends up on top
+        for (uri, width, height) in [
+            (format!("/api/v1/image/{image_id}"), 600, 1200),
+            (format!("/api/v1/image/{image_id}/small"), 480, 960),
+        ] {
+            let req = test::TestRequest::get().uri(&uri).to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/jpeg");
+            let body = test::read_body(resp).await;
+            assert!(!body.windows(4).any(|bytes| bytes == b"Exif"), "{uri}");
+            assert!(!body.windows(LATITUDE_SECONDS.len()).any(|bytes| bytes == LATITUDE_SECONDS), "{uri}");
+
+            let image = image::load_from_memory(&body).expect("decoding the image").to_rgb8();
+            assert_eq!(image.dimensions(), (width, height), "{uri}");
+            let top = image.get_pixel(width / 2, height / 4);
+            let bottom = image.get_pixel(width / 2, height * 3 / 4);
+            assert!(top[0] > 200 && top[2] < 50, "{uri}");
+            assert!(bottom[0] < 50 && bottom[2] > 200, "{uri}");
+        }
+
+        // WebP images keep their frames and lose the EXIF chunk
+        let req = test::TestRequest::put()
+            .uri("/api/v1/users/me/avatar")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_multipart(None, "image", &encode_webp_with_location(16, 8)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let profile: ProfileDTO = test::read_body_json(resp).await;
+        let avatar = profile.avatar.expect("avatar is set");
+
+        let req = test::TestRequest::get().uri(&format!("/api/v1/image/{avatar}")).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/webp");
+        let body = test::read_body(resp).await;
+        assert!(!body.windows(4).any(|bytes| bytes == b"EXIF"));
+        assert!(!body.windows(LATITUDE_SECONDS.len()).any(|bytes| bytes == LATITUDE_SECONDS));
+        assert_eq!(&body[12..16], b"VP8X");
+        assert_eq!(body[20] & 0x08, 0, "EXIF flag is cleared");
+        let image = image::load_from_memory(&body).expect("decoding the avatar");
+        assert_eq!((image.width(), image.height()), (16, 8));
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{}", post.id))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        fs::remove_file(format!("images/{avatar}")).await.expect("removing avatar");
+        fs::remove_file(format!("images/{avatar}.meta")).await.expect("removing avatar metadata");
     }
 }
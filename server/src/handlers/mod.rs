@@ -1,4 +1,19 @@
 pub mod blogpost_handler;
+pub mod comment_handler;
 pub mod image_handler;
+pub mod reaction_handler;
+pub mod search_handler;
+pub mod syndication_handler;
+pub mod tag_handler;
+pub mod user_handler;
+/// fixtures shared by the handler tests
+#[cfg(test)]
+mod test_utils;
 mod image_handler_tests;
 mod blogpost_handler_tests;
+mod user_handler_tests;
+mod comment_handler_tests;
+mod reaction_handler_tests;
+mod tag_handler_tests;
+mod search_handler_tests;
+mod syndication_handler_tests;
@@ -0,0 +1,80 @@
+use actix_web::{delete, post, web, HttpResponse, Responder};
+use log::{log, Level};
+use crate::auth::AuthenticatedUser;
+use crate::db::DBPool;
+use crate::models::{GenericErrorMessageDTO, REACTION_KINDS};
+use crate::service::reaction_service::{self, NewReactionOutcome};
+
+fn unknown_kind_response() -> HttpResponse {
+    let err_dto = GenericErrorMessageDTO::new(format!("Reaction must be one of: {}!", REACTION_KINDS.join(", ")));
+    HttpResponse::BadRequest().json(err_dto)
+}
+
+/// reacts to the blogpost as the authenticated user, every kind can be used only once per blogpost
+#[post("/api/v1/blogpost/{id}/reactions/{kind}")]
+async fn add_reaction(user: AuthenticatedUser, path: web::Path<(i32, String)>, pool: web::Data<DBPool>) -> impl Responder {
+    let (post_id, kind) = path.into_inner();
+    if !REACTION_KINDS.contains(&kind.as_str()) { return unknown_kind_response(); }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || reaction_service::add_reaction(&mut conn, post_id, user.id, &kind)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error saving a reaction on blogpost {}: {}", post_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error saving a reaction on blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        NewReactionOutcome::Added => HttpResponse::Created().finish(),
+        NewReactionOutcome::AlreadyReacted => {
+            let err_dto = GenericErrorMessageDTO::new("You already reacted to this blogpost with the same reaction!".to_string());
+            HttpResponse::Conflict().json(err_dto)
+        }
+        NewReactionOutcome::PostNotFound => {
+            let err_dto = GenericErrorMessageDTO::new(format!("Blogpost {post_id} does not exist!"));
+            HttpResponse::NotFound().json(err_dto)
+        }
+    }
+}
+
+/// removes the reaction of the authenticated user from the blogpost
+#[delete("/api/v1/blogpost/{id}/reactions/{kind}")]
+async fn remove_reaction(user: AuthenticatedUser, path: web::Path<(i32, String)>, pool: web::Data<DBPool>) -> impl Responder {
+    let (post_id, kind) = path.into_inner();
+    if !REACTION_KINDS.contains(&kind.as_str()) { return unknown_kind_response(); }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || reaction_service::remove_reaction(&mut conn, post_id, user.id, &kind)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error deleting a reaction on blogpost {}: {}", post_id, e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error deleting a reaction on blogpost {}: {}", post_id, crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    if res.unwrap() {
+        HttpResponse::NoContent().finish()
+    } else {
+        let err_dto = GenericErrorMessageDTO::new("You have not reacted to this blogpost with this reaction!".to_string());
+        HttpResponse::NotFound().json(err_dto)
+    }
+}
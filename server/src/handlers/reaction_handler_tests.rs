@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web::{self, Data}, App};
+    use crate::{
+        auth::authenticate,
+        db::establish_connection_pool,
+        handlers::{
+            blogpost_handler::{get_blogpost, get_feed},
+            reaction_handler::{add_reaction, remove_reaction},
+            test_utils::{create_user_session, create_user_with_draft, create_user_with_post}},
+        models::{BlogPost, FeedDTO}};
+
+    // TESTS NEED TO BE RAN SEQUENTIALLY
+
+    #[actix_web::test]
+    async fn test_add_and_remove_reactions() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let ((user, post_id), (other_user, _)) = web::block(move || -> anyhow::Result<_> {
+            Ok((create_user_with_post(&mut conn)?, create_user_with_post(&mut conn)?))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating users with posts");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(add_reaction)
+            .service(remove_reaction)
+            .service(get_blogpost)
+            .service(get_feed)
+        ).await;
+
+        for (token, kind) in [(&user.token, "like"), (&other_user.token, "like"), (&user.token, "love")] {
+            let req = test::TestRequest::post()
+                .uri(&format!("/api/v1/blogpost/{post_id}/reactions/{kind}"))
+                .insert_header(("Authorization", format!("Bearer {token}")))
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+        }
+
+        // the same kind only once per user
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/like"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{post_id}"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let post: BlogPost = test::read_body_json(resp).await;
+        assert_eq!(post.reactions.0.get("like"), Some(&2));
+        assert_eq!(post.reactions.0.get("love"), Some(&1));
+        assert_eq!(post.reactions.0.len(), 2);
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/like"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/like"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // counts are part of every blogpost in the feed
+        let req = test::TestRequest::get()
+            .uri("/api/v1/blogpost?page=1&limit=50")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let feed: FeedDTO = test::read_body_json(resp).await;
+        let post = feed.blogposts.iter().find(|post| post.id == post_id).expect("post is in the feed");
+        assert_eq!(post.reactions.0.get("like"), Some(&1));
+        assert_eq!(post.reactions.0.get("love"), Some(&1));
+        let other_post = feed.blogposts.iter().find(|post| post.user_id == Some(other_user.id)).expect("post is in the feed");
+        assert!(other_post.reactions.0.is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_reactions() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (user, post_id) = web::block(move || create_user_with_post(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user with a post");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(add_reaction)
+            .service(remove_reaction)
+        ).await;
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/dislike"))
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/blogpost/-1/reactions/like")
+            .insert_header(("Authorization", format!("Bearer {}", user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/like"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_draft_reactions() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let ((author, post_id), other_user) = web::block(move || -> anyhow::Result<_> {
+            Ok((create_user_with_draft(&mut conn)?, create_user_session(&mut conn)?))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating users with a draft");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(add_reaction)
+        ).await;
+
+        // drafts do not exist for anyone but their author
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/like"))
+            .insert_header(("Authorization", format!("Bearer {}", other_user.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/v1/blogpost/{post_id}/reactions/like"))
+            .insert_header(("Authorization", format!("Bearer {}", author.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+}
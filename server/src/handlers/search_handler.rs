@@ -0,0 +1,63 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use log::{log, Level};
+use crate::db::DBPool;
+use crate::models::{SearchCursor, SearchDTO, SearchQuery, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE, MAX_SEARCH_QUERY_SIZE};
+use crate::service::search_service::{self, SearchPosition};
+
+/// full-text search over the text and the author of the blogposts,
+/// paginated the same way as the feed
+#[get("/api/v1/search")]
+async fn search(req: HttpRequest, pool: web::Data<DBPool>) -> impl Responder {
+    let params = web::Query::<SearchQuery>::from_query(req.query_string());
+    if params.is_err() { return HttpResponse::BadRequest().finish(); }
+    let params = params.unwrap().into_inner();
+
+    let query = params.q.trim().to_string();
+    if query.is_empty() || query.len() > MAX_SEARCH_QUERY_SIZE { return HttpResponse::BadRequest().finish(); }
+
+    let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) { return HttpResponse::BadRequest().finish(); }
+
+    // exactly one of page and cursor has to be present
+    let position = match (params.page, params.cursor) {
+        (Some(page), None) => {
+            if page < 1 { return HttpResponse::BadRequest().finish(); }
+            SearchPosition::Page(page)
+        }
+        (None, Some(cursor)) => {
+            let cursor = SearchCursor::decode(&cursor);
+            if cursor.is_err() { return HttpResponse::BadRequest().finish(); }
+            SearchPosition::After(cursor.unwrap())
+        }
+        _ => return HttpResponse::BadRequest().finish(),
+    };
+
+    let page = match position {
+        SearchPosition::Page(page) => Some(page),
+        SearchPosition::After(_) => None,
+    };
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || -> anyhow::Result<_> {
+        let (results, next_cursor) = search_service::search_blogposts(&mut conn, &query, position, limit)?;
+        let total_count = search_service::count_search_results(&mut conn, &query)?;
+        Ok(SearchDTO::new(results, next_cursor, page, limit, total_count))
+    }).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error searching blogposts: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error searching blogposts: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().json(res.unwrap())
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use actix_web::{http::StatusCode, test, web::{self, Data}, App};
+    use uuid::Uuid;
+    use crate::{
+        db::establish_connection_pool,
+        handlers::{search_handler::search, test_utils::create_user_with_posts},
+        models::SearchDTO};
+
+    // TESTS NEED TO BE RAN SEQUENTIALLY
+
+    #[actix_web::test]
+    async fn test_search() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        // unique word so other posts do not match
+        let word = format!("word{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let texts = [
+            format!("Once {word} & 1 < 2 is a \"quoted\" text"),
+            format!("{word} is repeated, {word} again"),
+            "Nothing to see here".to_string(),
+        ];
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_with_posts(&mut conn, &texts))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user with posts");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(search)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/search?q={word}&page=1&limit=1"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let first: SearchDTO = test::read_body_json(resp).await;
+        assert_eq!(first.total_count, 2);
+        assert_eq!(first.results.len(), 1);
+        assert!(first.has_next);
+        // the post that mentions the word more times ranks higher
+        assert!(first.results[0].blogpost.excerpt.0.starts_with(&format!("{word} is repeated")));
+        assert!(first.results[0].snippet.contains(&format!("<mark>{word}</mark>")));
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/search?q={word}&limit=1&cursor={}", first.next_cursor.expect("there is a next page")))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let second: SearchDTO = test::read_body_json(resp).await;
+        assert_eq!(second.results.len(), 1);
+        assert!(!second.has_next);
+        assert!(second.results[0].rank <= first.results[0].rank);
+        // the rest of the text is escaped
+        assert_eq!(second.results[0].snippet, format!("Once <mark>{word}</mark> &amp; 1 &lt; 2 is a &quot;quoted&quot; text"));
+
+        // authors can be searched for as well
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/search?q=%22{}%22&page=1", user.username))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let by_author: SearchDTO = test::read_body_json(resp).await;
+        assert_eq!(by_author.total_count, 3);
+        assert!(by_author.results.iter().all(|result| result.blogpost.username == user.username));
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_search() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(search)
+        ).await;
+
+        let long_query = "a".repeat(257);
+        let invalid = [
+            "page=1",
+            "q=%20&page=1",
+            "q=word",
+            "q=word&page=0",
+            "q=word&page=1&limit=51",
+            "q=word&cursor=invalid",
+            &format!("q={long_query}&page=1"),
+        ];
+        for query in invalid {
+            let req = test::TestRequest::get()
+                .uri(&format!("/api/v1/search?{query}"))
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use actix_web::{get, web, HttpRequest, HttpResponse, Responder};
+use log::{log, Level};
+use crate::config::Config;
+use crate::db::DBPool;
+use crate::models::{normalize_tag, SyndicationQuery};
+use crate::service::blogpost_service::FeedFilter;
+use crate::service::syndication_service::{self, Syndication};
+
+const RSS_CONTENT_TYPE: &str = "application/rss+xml; charset=utf-8";
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+const JSON_FEED_CONTENT_TYPE: &str = "application/feed+json; charset=utf-8";
+
+/// reads the newest blogposts matching the query and writes them with `write`
+async fn syndication_response(
+    req: &HttpRequest,
+    pool: &DBPool,
+    config: &Config,
+    content_type: &'static str,
+    write: fn(&Syndication) -> anyhow::Result<String>) -> HttpResponse {
+    let params = web::Query::<SyndicationQuery>::from_query(req.query_string());
+    if params.is_err() { return HttpResponse::BadRequest().finish(); }
+    let params = params.unwrap().into_inner();
+
+    let mut filter = FeedFilter {
+        username: params.author,
+        ..Default::default()
+    };
+    if let Some(tag) = params.tag {
+        let tag = normalize_tag(&tag);
+        if tag.is_none() { return HttpResponse::BadRequest().finish(); }
+        filter.tag = tag;
+    }
+
+    let mut self_url = format!("{}{}", config.public_url, req.path());
+    if !req.query_string().is_empty() {
+        self_url.push('?');
+        self_url.push_str(req.query_string());
+    }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let public_url = config.public_url.clone();
+    let res = web::block(move || -> anyhow::Result<_> {
+        let syndication = syndication_service::get_syndication(&mut conn, &filter, &public_url, self_url)?;
+        write(&syndication)
+    }).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error writing a feed: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error writing a feed: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok()
+        .content_type(content_type)
+        .body(res.unwrap())
+}
+
+/// RSS 2.0 feed of the newest blogposts, optionally filtered by `tag` and `author`
+#[get("/feed.rss")]
+async fn rss_feed(req: HttpRequest, pool: web::Data<DBPool>, config: web::Data<Config>) -> impl Responder {
+    syndication_response(&req, &pool, &config, RSS_CONTENT_TYPE, Syndication::to_rss).await
+}
+
+/// Atom feed of the newest blogposts, optionally filtered by `tag` and `author`
+#[get("/feed.atom")]
+async fn atom_feed(req: HttpRequest, pool: web::Data<DBPool>, config: web::Data<Config>) -> impl Responder {
+    syndication_response(&req, &pool, &config, ATOM_CONTENT_TYPE, Syndication::to_atom).await
+}
+
+/// JSON Feed 1.1 of the newest blogposts, optionally filtered by `tag` and `author`
+#[get("/feed.json")]
+async fn json_feed(req: HttpRequest, pool: web::Data<DBPool>, config: web::Data<Config>) -> impl Responder {
+    syndication_response(&req, &pool, &config, JSON_FEED_CONTENT_TYPE, Syndication::to_json_feed).await
+}
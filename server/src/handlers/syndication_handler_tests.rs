@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use actix_web::{http::StatusCode, test, web::{self, Data}, App};
+    use uuid::Uuid;
+    use crate::{
+        config::Config,
+        db::establish_connection_pool,
+        handlers::{syndication_handler::{atom_feed, json_feed, rss_feed}, test_utils::create_user_session},
+        models::{CreateBlogPostDTO, JsonFeedDTO, JSON_FEED_VERSION},
+        service::{blogpost_service, user_service}};
+
+    // TESTS NEED TO BE RAN SEQUENTIALLY
+
+    #[actix_web::test]
+    async fn test_rss_and_atom_feeds() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        // unique tag so other tests do not affect the feed
+        let tag = format!("feed-{}", &Uuid::new_v4().simple().to_string()[..8]);
+        let tag_clone = tag.clone();
+        let mut conn = connection_pool.get().expect("getting connection");
+        let author = web::block(move || {
+            let author = create_user_session(&mut conn)?;
+            let dto = CreateBlogPostDTO {
+                text: "Tagged post\nwith <b>two</b> lines".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, vec![tag_clone], &author, None)?;
+            let dto = CreateBlogPostDTO {
+                text: "Post with an image".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, Vec::new(), &author, Some("placeholder_avatar".to_string()))?;
+            Ok::<_, anyhow::Error>(author)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating blogposts");
+
+        let config = Config::default();
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(config.clone()))
+            .service(rss_feed)
+            .service(atom_feed)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/feed.rss?author={}", author.username))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/rss+xml; charset=utf-8");
+        let body = test::read_body(resp).await;
+        let channel = rss::Channel::read_from(&body[..]).expect("parsing rss feed");
+        assert_eq!(channel.title, format!("SimpleBlog by {}", author.username));
+        assert_eq!(channel.items.len(), 2);
+
+        let image_item = &channel.items[0];
+        let image_size = std::fs::metadata("images/placeholder_avatar").expect("reading image size").len();
+        let enclosure = image_item.enclosure.as_ref().expect("enclosure is present");
+        assert_eq!(enclosure.url, format!("{}/api/v1/image/placeholder_avatar", config.public_url));
+        assert_eq!(enclosure.length, image_size.to_string());
+        assert_eq!(enclosure.mime_type, "image/png");
+        let creators = &image_item.dublin_core_ext.as_ref().expect("dublin core is present").creators;
+        assert_eq!(creators, &vec![author.username.clone()]);
+        assert!(image_item.pub_date.is_some());
+
+        let tagged_item = &channel.items[1];
+        assert!(tagged_item.enclosure.is_none());
+        assert_eq!(tagged_item.description.as_deref(), Some("<p>Tagged post\nwith <b>two</b> lines</p>"));
+        assert_eq!(tagged_item.categories[0].name, tag);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/feed.atom?tag={tag}&author={}", author.username))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/atom+xml; charset=utf-8");
+        let body = test::read_body(resp).await;
+        let feed = atom_syndication::Feed::read_from(&body[..]).expect("parsing atom feed");
+        assert_eq!(feed.title.value, format!("SimpleBlog #{tag} by {}", author.username));
+        assert_eq!(feed.entries.len(), 1);
+        let entry = &feed.entries[0];
+        assert_eq!(entry.title.value, "Tagged post with two lines");
+        assert_eq!(entry.authors[0].name, author.username);
+        assert_eq!(entry.content.as_ref().and_then(|content| content.value.as_deref()),
+            Some("<p>Tagged post\nwith <b>two</b> lines</p>\n"));
+        assert!(entry.links.iter().all(|link| link.rel != "enclosure"));
+
+        let req = test::TestRequest::get()
+            .uri("/feed.atom?tag=white%20space")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_json_feed() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let author = web::block(move || {
+            let author = create_user_session(&mut conn)?;
+            user_service::set_avatar(&mut conn, author.id, "placeholder_avatar".to_string())?;
+            let dto = CreateBlogPostDTO {
+                text: "Post with an image".to_string(),
+                title: None,
+                tags: Vec::new(),
+                status: None,
+                publish_at: None,
+            };
+            blogpost_service::create_blogpost(&mut conn, dto, vec!["json".to_string()], &author, Some("placeholder_avatar".to_string()))?;
+            Ok::<_, anyhow::Error>(author)
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a blogpost");
+
+        let config = Config::default();
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(config.clone()))
+            .service(json_feed)
+        ).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/feed.json?author={}", author.username))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/feed+json; charset=utf-8");
+        let feed: JsonFeedDTO = test::read_body_json(resp).await;
+        assert_eq!(feed.version, JSON_FEED_VERSION);
+        assert_eq!(feed.feed_url, Some(format!("{}/feed.json?author={}", config.public_url, author.username)));
+        assert_eq!(feed.items.len(), 1);
+
+        let item = &feed.items[0];
+        let image_url = format!("{}/api/v1/image/placeholder_avatar", config.public_url);
+        assert_eq!(item.content_text, "Post with an image");
+        assert_eq!(item.content_html, "<p>Post with an image</p>\n");
+        assert_eq!(item.image.as_ref(), Some(&image_url));
+        assert_eq!(item.authors[0].name, author.username);
+        assert_eq!(item.authors[0].avatar.as_ref(), Some(&image_url));
+        assert_eq!(item.tags, ["json"]);
+        assert!(item.date_modified.is_none());
+    }
+}
@@ -0,0 +1,28 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use log::{log, Level};
+use crate::db::DBPool;
+use crate::service::tag_service;
+
+/// returns all used tags with the number of blogposts that have them
+#[get("/api/v1/tags")]
+async fn get_tags(pool: web::Data<DBPool>) -> impl Responder {
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || tag_service::get_tags(&mut conn)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting tags: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting tags: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().json(res.unwrap())
+}
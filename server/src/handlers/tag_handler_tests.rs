@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, io::Write};
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web::{self, Data}, App};
+    use serde_json::to_string;
+    use uuid::Uuid;
+    use crate::{
+        auth::authenticate,
+        config::Config,
+        db::establish_connection_pool,
+        handlers::{blogpost_handler::{create_blogpost, get_feed}, tag_handler::get_tags, test_utils::create_user_session},
+        models::{CreateBlogPostDTO, FeedDTO, TagDTO}};
+
+    // TESTS NEED TO BE RAN SEQUENTIALLY
+
+    /// helper function to manually construct a multipart form payload without a post image
+    fn create_multipart(dto: &CreateBlogPostDTO) -> Vec<u8> {
+        let boundary = "my_boundary";
+        let mut body = Vec::new();
+
+        write!(
+            &mut body,
+            "--{}\r\n\
+            Content-Disposition: form-data; name=\"data\"\r\n\r\n\
+        {}\r\n",
+        boundary, to_string(dto).expect("turning dto to json string")).unwrap();
+
+        write!(
+            &mut body,
+            "--{}--\r\n",
+            boundary).unwrap();
+
+        body
+    }
+
+    #[actix_web::test]
+    async fn test_tagged_feed() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(get_feed)
+            .service(get_tags)
+        ).await;
+
+        // unique tags so other tests do not affect the counts
+        let suffix = &Uuid::new_v4().simple().to_string()[..8];
+        let (common, rare) = (format!("common-{suffix}"), format!("rare-{suffix}"));
+        let posts = [
+            vec![format!(" #{} ", common.to_uppercase()), common.clone(), rare.clone()],
+            vec![common.clone()],
+            vec![],
+        ];
+        for tags in posts {
+            let dto = CreateBlogPostDTO { text: "Hello!".to_string(), title: None, tags, status: None, publish_at: None };
+            let req = test::TestRequest::post()
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(create_multipart(&dto))
+                .uri("/api/v1/blogpost")
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::CREATED);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost?page=1&tag={}", common.to_uppercase()))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let feed: FeedDTO = test::read_body_json(resp).await;
+        assert_eq!(feed.total_count, 2);
+        assert_eq!(feed.blogposts.len(), 2);
+        assert_eq!(feed.blogposts[0].tags, vec![common.clone()]);
+        assert_eq!(feed.blogposts[1].tags, [common.clone(), rare.clone()]);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/tags")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let tags: Vec<TagDTO> = test::read_body_json(resp).await;
+        let count = |name: &str| tags.iter().find(|tag| tag.name == name).map(|tag| tag.count);
+        assert_eq!(count(&common), Some(2));
+        assert_eq!(count(&rare), Some(1));
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_tags() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let mut conn = connection_pool.get().expect("getting connection");
+        let user = web::block(move || create_user_session(&mut conn))
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(create_blogpost)
+            .service(get_feed)
+        ).await;
+
+        let invalid = [
+            vec!["".to_string()],
+            vec!["white space".to_string()],
+            vec!["a".repeat(33)],
+            (0..11).map(|i| format!("tag{i}")).collect(),
+        ];
+        for tags in invalid {
+            let dto = CreateBlogPostDTO { text: "Hello!".to_string(), title: None, tags, status: None, publish_at: None };
+            let req = test::TestRequest::post()
+                .insert_header(("Authorization", format!("Bearer {}", user.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(create_multipart(&dto))
+                .uri("/api/v1/blogpost")
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/blogpost?page=1&tag=white%20space")
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
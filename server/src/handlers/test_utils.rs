@@ -0,0 +1,63 @@
+use anyhow::Result;
+use diesel::PgConnection;
+use uuid::Uuid;
+use crate::{
+    auth::AuthenticatedUser,
+    models::{CreateBlogPostDTO, CredentialsDTO, STATUS_DRAFT},
+    service::{blogpost_service, user_service}};
+
+/// registers a user with a random username and logs them in
+pub fn create_user_session(conn: &mut PgConnection) -> Result<AuthenticatedUser> {
+    let credentials = || CredentialsDTO {
+        username: format!("user-{}", Uuid::new_v4()),
+        password: "password".to_string(),
+    };
+    let dto = credentials();
+    let username = dto.username.clone();
+    let user = user_service::create_user(conn, dto)?.expect("username is unique");
+    let session = user_service::login(conn, CredentialsDTO { username, ..credentials() })?
+        .expect("credentials are valid");
+
+    Ok(AuthenticatedUser {
+        id: user.id,
+        username: user.username,
+        token: session.token,
+    })
+}
+
+/// registers a user with a random username, logs them in and creates published blogposts with the given texts
+pub fn create_user_with_posts(conn: &mut PgConnection, texts: &[String]) -> Result<AuthenticatedUser> {
+    let user = create_user_session(conn)?;
+    for text in texts {
+        let dto = CreateBlogPostDTO { text: text.clone(), title: None, tags: Vec::new(), status: None, publish_at: None };
+        blogpost_service::create_blogpost(conn, dto, Vec::new(), &user, None)?;
+    }
+
+    Ok(user)
+}
+
+/// registers a user with a random username, logs them in and creates a published blogpost
+/// returns the user and the id of the blogpost
+pub fn create_user_with_post(conn: &mut PgConnection) -> Result<(AuthenticatedUser, i32)> {
+    let user = create_user_session(conn)?;
+    let dto = CreateBlogPostDTO { text: "Hello!".to_string(), title: None, tags: Vec::new(), status: None, publish_at: None };
+    let post = blogpost_service::create_blogpost(conn, dto, Vec::new(), &user, None)?;
+
+    Ok((user, post.id))
+}
+
+/// registers a user with a random username, logs them in and creates a draft blogpost
+/// returns the user and the id of the blogpost
+pub fn create_user_with_draft(conn: &mut PgConnection) -> Result<(AuthenticatedUser, i32)> {
+    let user = create_user_session(conn)?;
+    let dto = CreateBlogPostDTO {
+        text: "Not ready yet".to_string(),
+        title: None,
+        tags: Vec::new(),
+        status: Some(STATUS_DRAFT.to_string()),
+        publish_at: None,
+    };
+    let post = blogpost_service::create_blogpost(conn, dto, Vec::new(), &user, None)?;
+
+    Ok((user, post.id))
+}
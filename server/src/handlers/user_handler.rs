@@ -0,0 +1,290 @@
+use actix_multipart::Multipart;
+use actix_web::{get, post, put, web, HttpResponse, Responder};
+use futures_util::TryStreamExt;
+use log::{log, Level};
+use reqwest::Url;
+use crate::auth::AuthenticatedUser;
+use crate::config::Config;
+use crate::db::DBPool;
+use crate::handlers::blogpost_handler::{clear_files, drain_data, drain_fields};
+use crate::models::{AVATAR_VARIANTS, CredentialsDTO, GenericErrorMessageDTO, ProfileDTO, UpdateProfileDTO, UserDTO};
+use crate::service::image_service::{delete_image, download_avatar, save_image};
+use crate::service::user_service;
+
+#[post("/api/v1/users/register")]
+async fn register(dto: web::Json<CredentialsDTO>, pool: web::Data<DBPool>) -> impl Responder {
+    let dto = dto.into_inner();
+    if !dto.is_valid() {
+        let err_dto = GenericErrorMessageDTO::new(
+            "Username must be up to 128 letters, digits, '_', '-' or '.', not a reserved name, and password between 8 and 128 characters!".to_string());
+        return HttpResponse::BadRequest().json(err_dto);
+    }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || user_service::create_user(&mut conn, dto)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error creating a user: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error creating a user: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        Some(user) => HttpResponse::Created().json(UserDTO { id: user.id, username: user.username }),
+        None => {
+            let err_dto = GenericErrorMessageDTO::new("Username is already taken!".to_string());
+            HttpResponse::Conflict().json(err_dto)
+        }
+    }
+}
+
+/// returns a session token that has to be sent in the Authorization header
+#[post("/api/v1/users/login")]
+async fn login(dto: web::Json<CredentialsDTO>, pool: web::Data<DBPool>) -> impl Responder {
+    let dto = dto.into_inner();
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || user_service::login(&mut conn, dto)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error logging in: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error logging in: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        Some(session) => HttpResponse::Ok().json(session),
+        None => {
+            let err_dto = GenericErrorMessageDTO::new("Wrong username or password!".to_string());
+            HttpResponse::Unauthorized().json(err_dto)
+        }
+    }
+}
+
+/// ends the session the request was authenticated with
+#[post("/api/v1/users/logout")]
+async fn logout(user: AuthenticatedUser, pool: web::Data<DBPool>) -> impl Responder {
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || user_service::logout(&mut conn, &user.token)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error logging out: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    if let Err(e) = res.unwrap() {
+        log!(Level::Error, "Error logging out: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::NoContent().finish()
+}
+
+/// returns the authenticated user
+#[get("/api/v1/users/me")]
+async fn me(user: AuthenticatedUser) -> impl Responder {
+    HttpResponse::Ok().json(UserDTO { id: user.id, username: user.username })
+}
+
+#[get("/api/v1/users/{username}/profile")]
+async fn get_profile(username: web::Path<String>, pool: web::Data<DBPool>) -> impl Responder {
+    let username = username.into_inner();
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || user_service::get_user_by_username(&mut conn, &username)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting a profile: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error getting a profile: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    match res.unwrap() {
+        Some(user) => HttpResponse::Ok().json(ProfileDTO::from(user)),
+        None => {
+            let err_dto = GenericErrorMessageDTO::new("User does not exist!".to_string());
+            HttpResponse::NotFound().json(err_dto)
+        }
+    }
+}
+
+/// replaces the display name and the bio of the authenticated user
+#[put("/api/v1/users/me/profile")]
+async fn update_profile(user: AuthenticatedUser, dto: web::Json<UpdateProfileDTO>, pool: web::Data<DBPool>) -> impl Responder {
+    let dto = dto.into_inner();
+    if !dto.is_valid() {
+        let err_dto = GenericErrorMessageDTO::new(
+            "Display name must be up to 128 and bio up to 500 characters!".to_string());
+        return HttpResponse::BadRequest().json(err_dto);
+    }
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let res = web::block(move || user_service::update_profile(&mut conn, user.id, dto)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error updating a profile: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error updating a profile: {}", crate::unroll_anyhow_result(e));
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok().json(ProfileDTO::from(res.unwrap()))
+}
+
+const INVALID_IMAGE_MESSAGE: &str = "Image must be in one of the accepted formats and not larger than 2MB!";
+
+/// uploads or replaces the avatar of the authenticated user,
+/// the avatar is either uploaded in the "image" field or downloaded from the url in the "url" field
+///
+/// the replaced avatar is deleted, all posts of the user show the new one
+#[put("/api/v1/users/me/avatar")]
+async fn set_avatar(
+    user: AuthenticatedUser,
+    mut payload: Multipart,
+    pool: web::Data<DBPool>,
+    config: web::Data<Config>) -> impl Responder {
+    let mut avatar_uuid: Option<String> = None;
+
+    // force closing connection on every early return while there is still data to read,
+    // otherwise connection will hang indefinitely
+    while let Ok(Some(mut field)) = payload.try_next().await {
+        let field_name = field.content_disposition()
+            .and_then(|content_disposition| content_disposition.get_name())
+            .map(str::to_string);
+        // only a single avatar can be sent
+        if field_name.is_none() || avatar_uuid.is_some() {
+            clear_files(avatar_uuid, None).await;
+            drain_data(&mut payload, &mut field).await;
+            return HttpResponse::BadRequest().force_close().finish();
+        }
+
+        match field_name.unwrap().as_str() {
+            "image" => {
+                let image_result = save_image(&mut field, &config.image_formats, &AVATAR_VARIANTS).await;
+                if let Err(e) = image_result {
+                    log!(Level::Error, "Error saving an avatar: {}", crate::unroll_anyhow_result(e));
+                    drain_fields(&mut payload).await;
+                    return HttpResponse::InternalServerError().force_close().finish();
+                }
+                let (image_result, too_large, is_allowed) = image_result.unwrap();
+                if too_large || !is_allowed {
+                    // rejected image is already deleted
+                    drain_fields(&mut payload).await;
+                    let err_dto = GenericErrorMessageDTO::new(INVALID_IMAGE_MESSAGE.to_string());
+                    return HttpResponse::BadRequest().force_close().json(err_dto);
+                }
+                avatar_uuid = Some(image_result);
+            }
+
+            "url" => {
+                let bytes = field.try_next().await;
+                if let Err(e) = bytes {
+                    log!(Level::Error, "Error reading data from the body: {}", e);
+                    drain_data(&mut payload, &mut field).await;
+                    return HttpResponse::InternalServerError().force_close().finish();
+                }
+                let avatar_url = bytes.unwrap()
+                    .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
+                    .filter(|avatar_url| Url::parse(avatar_url).is_ok());
+                if avatar_url.is_none() {
+                    drain_data(&mut payload, &mut field).await;
+                    let err_dto = GenericErrorMessageDTO::new(INVALID_IMAGE_MESSAGE.to_string());
+                    return HttpResponse::BadRequest().force_close().json(err_dto);
+                }
+
+                let res = download_avatar(&avatar_url.unwrap(), &config.image_formats, &AVATAR_VARIANTS).await;
+                if let Err(e) = res {
+                    log!(Level::Error, "Error downloading avatar: {}", crate::unroll_anyhow_result(e));
+                    drain_data(&mut payload, &mut field).await;
+                    return HttpResponse::InternalServerError().force_close().finish();
+                }
+                let res = res.unwrap();
+                if res.is_none() {
+                    drain_data(&mut payload, &mut field).await;
+                    let err_dto = GenericErrorMessageDTO::new(INVALID_IMAGE_MESSAGE.to_string());
+                    return HttpResponse::BadRequest().force_close().json(err_dto);
+                }
+                avatar_uuid = res;
+            }
+
+            _ => {
+                drain_data(&mut payload, &mut field).await;
+                return HttpResponse::BadRequest().force_close().finish();
+            }
+        }
+    }
+
+    // avatar cannot be missing
+    if avatar_uuid.is_none() { return HttpResponse::BadRequest().finish(); }
+    let avatar_uuid = avatar_uuid.unwrap();
+
+    let conn = pool.get();
+    if let Err(e) = conn {
+        log!(Level::Error, "Error getting a connection from pool: {}", e);
+        clear_files(Some(avatar_uuid), None).await;
+        return HttpResponse::InternalServerError().finish();
+    }
+    let mut conn = conn.unwrap();
+
+    let avatar_uuid_clone = avatar_uuid.clone();
+    let res = web::block(move || user_service::set_avatar(&mut conn, user.id, avatar_uuid_clone)).await;
+    if let Err(e) = res {
+        log!(Level::Error, "Error setting an avatar: {}", e);
+        clear_files(Some(avatar_uuid), None).await;
+        return HttpResponse::InternalServerError().finish();
+    }
+    let res = res.unwrap();
+    if let Err(e) = res {
+        log!(Level::Error, "Error setting an avatar: {}", crate::unroll_anyhow_result(e));
+        clear_files(Some(avatar_uuid), None).await;
+        return HttpResponse::InternalServerError().finish();
+    }
+    let (user, replaced_avatar) = res.unwrap();
+
+    if let Some(replaced_avatar) = replaced_avatar {
+        if let Err(e) = delete_image(replaced_avatar).await {
+            log!(Level::Error, "Error removing a replaced avatar: {}", crate::unroll_anyhow_result(e));
+        }
+    }
+
+    HttpResponse::Ok().json(ProfileDTO::from(user))
+}
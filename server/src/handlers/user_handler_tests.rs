@@ -0,0 +1,514 @@
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::{Cursor, Write}, path::Path};
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web::{self, Data}, App};
+    use chrono::{TimeDelta, Utc};
+    use diesel::{QueryDsl, RunQueryDsl};
+    use uuid::Uuid;
+    use crate::{
+        auth::{authenticate, AuthenticatedUser},
+        config::Config,
+        db::establish_connection_pool,
+        handlers::{
+            blogpost_handler::get_blogpost,
+            test_utils::create_user_with_post,
+            user_handler::{get_profile, login, logout, me, register, set_avatar, update_profile}},
+        models::{
+            BlogPost, CreateBlogPostDTO, CredentialsDTO, ImageFormat, NewSession, ProfileDTO, SessionDTO, UpdateProfileDTO, UserDTO
+        },
+        schema::SessionTable,
+        service::{blogpost_service, image_service, user_service}};
+
+    fn random_credentials() -> CredentialsDTO {
+        CredentialsDTO {
+            username: format!("user-{}", Uuid::new_v4()),
+            password: "password".to_string(),
+        }
+    }
+
+    /// helper function that encodes an 8x8 image in the given format
+    fn encode_image(format: image::ImageFormat) -> Vec<u8> {
+        let image = image::RgbImage::from_pixel(8, 8, image::Rgb([200, 100, 50]));
+        let mut encoded = Vec::new();
+        image.write_to(&mut Cursor::new(&mut encoded), format).expect("encoding an image");
+        encoded
+    }
+
+    /// helper function to manually construct a multipart form payload with a single field
+    fn create_avatar_multipart(field_name: &str, data: &[u8]) -> Vec<u8> {
+        let boundary = "my_boundary";
+        let mut body = Vec::new();
+
+        write!(
+            &mut body,
+            "--{}\r\n\
+            Content-Disposition: form-data; name=\"{}\"; filename=\"avatar.png\"\r\n\
+        Content-Type: image/png\r\n\r\n",
+        boundary, field_name).unwrap();
+        body.extend(data);
+        body.write_all(b"\r\n").unwrap();
+
+        write!(
+            &mut body,
+            "--{}--\r\n",
+            boundary).unwrap();
+
+        body
+    }
+
+    #[actix_web::test]
+    async fn test_register_login_logout() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(register)
+            .service(login)
+            .service(logout)
+            .service(me)
+        ).await;
+
+        let credentials = random_credentials();
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/register")
+            .set_json(&credentials)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let user: UserDTO = test::read_body_json(resp).await;
+        assert_eq!(user.username, credentials.username);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/login")
+            .set_json(&credentials)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let session: SessionDTO = test::read_body_json(resp).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/users/me")
+            .insert_header(("Authorization", format!("Bearer {}", session.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let current_user: UserDTO = test::read_body_json(resp).await;
+        assert_eq!(current_user.id, user.id);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/logout")
+            .insert_header(("Authorization", format!("Bearer {}", session.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        // token is no longer valid
+        let req = test::TestRequest::get()
+            .uri("/api/v1/users/me")
+            .insert_header(("Authorization", format!("Bearer {}", session.token)))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_register_taken_username() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(register)
+        ).await;
+
+        let credentials = random_credentials();
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/register")
+            .set_json(&credentials)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/register")
+            .set_json(&credentials)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+    }
+
+    #[actix_web::test]
+    async fn test_register_invalid_credentials() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(register)
+        ).await;
+
+        let invalid = [
+            CredentialsDTO { password: "short".to_string(), ..random_credentials() },
+            CredentialsDTO { username: "".to_string(), ..random_credentials() },
+            CredentialsDTO { username: "white space".to_string(), ..random_credentials() },
+            CredentialsDTO { username: "a".repeat(129), ..random_credentials() },
+            CredentialsDTO { username: "me".to_string(), ..random_credentials() },
+        ];
+        for credentials in invalid {
+            let req = test::TestRequest::post()
+                .uri("/api/v1/users/register")
+                .set_json(&credentials)
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_login_wrong_password() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let app = test::init_service(
+            App::new()
+            .app_data(Data::new(connection_pool.clone()))
+            .service(register)
+            .service(login)
+        ).await;
+
+        let credentials = random_credentials();
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/register")
+            .set_json(&credentials)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/users/login")
+            .set_json(CredentialsDTO { password: "wrong_password".to_string(), ..credentials })
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_session() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let pool = connection_pool.clone();
+        let (user, post_id) = web::block(move || {
+            let mut conn = pool.get().expect("getting a connection");
+            create_user_with_post(&mut conn)
+        }).await.unwrap().expect("creating a user with a post");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .service(me)
+            .service(get_blogpost)
+        ).await;
+
+        // public endpoints treat a stale token as anonymous
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/v1/blogpost/{}", post_id))
+            .insert_header(("Authorization", "Bearer stale-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let req = test::TestRequest::get()
+            .uri("/api/v1/users/me")
+            .insert_header(("Authorization", "Bearer stale-token"))
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // expired sessions are deleted, the valid one is kept
+        let pool = connection_pool.clone();
+        let user_id = user.id;
+        let token = user.token.clone();
+        let (expired_left, user_left) = web::block(move || -> anyhow::Result<_> {
+            let mut conn = pool.get()?;
+            let expired = NewSession {
+                token_hash: format!("expired-{}", Uuid::new_v4()),
+                user_id,
+                expires_at: Utc::now() - TimeDelta::minutes(1),
+            };
+            diesel::insert_into(SessionTable::table)
+                .values(&expired)
+                .execute(&mut conn)?;
+
+            user_service::delete_expired_sessions(&mut conn)?;
+
+            let expired_left = SessionTable::table
+                .find(&expired.token_hash)
+                .count()
+                .get_result::<i64>(&mut conn)?;
+            let user_left = user_service::get_session_user(&mut conn, &token)?;
+            Ok((expired_left, user_left))
+        }).await.unwrap().expect("deleting expired sessions");
+        assert_eq!(expired_left, 0);
+        assert_eq!(user_left.map(|user| user.id), Some(user.id));
+    }
+
+    #[actix_web::test]
+    async fn test_profile_avatar_shared_by_posts() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let credentials = random_credentials();
+        let profile_uri = format!("/api/v1/users/{}/profile", credentials.username);
+        let mut conn = connection_pool.get().expect("getting connection");
+        let (session, post_id) = web::block(move || -> anyhow::Result<_> {
+            use crate::schema::blogpost::{table as BlogpostTable, dsl as blogpost};
+            use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
+
+            let login_credentials = CredentialsDTO {
+                username: credentials.username.clone(),
+                password: credentials.password.clone(),
+            };
+            user_service::create_user(&mut conn, credentials)?.expect("username is unique");
+            let session = user_service::login(&mut conn, login_credentials)?.expect("credentials are valid");
+            let user = user_service::get_session_user(&mut conn, &session.token)?.expect("session is valid");
+            let author = AuthenticatedUser { id: user.id, username: user.username, token: session.token.clone() };
+            blogpost_service::create_blogpost(&mut conn, CreateBlogPostDTO { text: "Hello!".to_string(), title: None, tags: Vec::new(), status: None, publish_at: None }, Vec::new(), &author, None)?;
+            let post_id = BlogpostTable
+                .filter(blogpost::user_id.eq(author.id))
+                .select(blogpost::id)
+                .first::<i32>(&mut conn)?;
+            Ok((session, post_id))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user with a post");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(get_profile)
+            .service(update_profile)
+            .service(set_avatar)
+            .service(get_blogpost)
+        ).await;
+
+        let avatar_data = fs::read("images/placeholder_avatar").expect("reading placeholder avatar");
+        let mut avatars = Vec::new();
+        // the second upload replaces the first avatar
+        for _ in 0..2 {
+            let req = test::TestRequest::put()
+                .uri("/api/v1/users/me/avatar")
+                .insert_header(("Authorization", format!("Bearer {}", session.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(create_avatar_multipart("image", &avatar_data))
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+            let profile: ProfileDTO = test::read_body_json(resp).await;
+            let avatar = profile.avatar.expect("avatar is set");
+            assert!(Path::new(&format!("images/{avatar}")).exists());
+            assert_eq!(image_service::image_meta(&avatar).expect("reading image metadata").unwrap().mime_type, "image/png");
+
+            // the post shows the current avatar
+            let req = test::TestRequest::get()
+                .uri(&format!("/api/v1/blogpost/{post_id}"))
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert!(resp.status().is_success());
+            let post: BlogPost = test::read_body_json(resp).await;
+            assert_eq!(post.avatar.as_ref(), Some(&avatar));
+
+            avatars.push(avatar);
+        }
+        assert!(!Path::new(&format!("images/{}", avatars[0])).exists());
+        assert!(!Path::new(&format!("images/{}.meta", avatars[0])).exists());
+
+        let dto = UpdateProfileDTO {
+            display_name: Some("Display Name".to_string()),
+            bio: Some("Bio".to_string()),
+        };
+        let req = test::TestRequest::put()
+            .uri("/api/v1/users/me/profile")
+            .insert_header(("Authorization", format!("Bearer {}", session.token)))
+            .set_json(&dto)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let req = test::TestRequest::get()
+            .uri(&profile_uri)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let profile: ProfileDTO = test::read_body_json(resp).await;
+        assert_eq!(profile.display_name, dto.display_name);
+        assert_eq!(profile.bio, dto.bio);
+        assert_eq!(profile.avatar.as_ref(), Some(&avatars[1]));
+
+        fs::remove_file(format!("images/{}", avatars[1])).expect("removing avatar");
+        fs::remove_file(format!("images/{}.meta", avatars[1])).expect("removing avatar metadata");
+    }
+
+    #[actix_web::test]
+    async fn test_invalid_avatar() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let credentials = random_credentials();
+        let profile_uri = format!("/api/v1/users/{}/profile", credentials.username);
+        let mut conn = connection_pool.get().expect("getting connection");
+        let session = web::block(move || -> anyhow::Result<_> {
+            let login_credentials = CredentialsDTO {
+                username: credentials.username.clone(),
+                password: credentials.password.clone(),
+            };
+            user_service::create_user(&mut conn, credentials)?.expect("username is unique");
+            Ok(user_service::login(&mut conn, login_credentials)?.expect("credentials are valid"))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(Config::default()))
+            .service(get_profile)
+            .service(set_avatar)
+        ).await;
+
+        let invalid = [
+            create_avatar_multipart("image", b"not a png image"),
+            create_avatar_multipart("url", b"not a url"),
+            create_avatar_multipart("unknown", b""),
+        ];
+        for form in invalid {
+            let req = test::TestRequest::put()
+                .uri("/api/v1/users/me/avatar")
+                .insert_header(("Authorization", format!("Bearer {}", session.token)))
+                .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+                .set_payload(form)
+                .to_request();
+
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let req = test::TestRequest::get()
+            .uri(&profile_uri)
+            .to_request();
+
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+        let profile: ProfileDTO = test::read_body_json(resp).await;
+        assert!(profile.avatar.is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_avatar_formats() {
+        let db_url = env::var("DB_URL")
+            .expect("missing DB_URL in env");
+        let connection_pool = establish_connection_pool(db_url)
+            .expect("making a connection pool");
+
+        let credentials = random_credentials();
+        let mut conn = connection_pool.get().expect("getting connection");
+        let session = web::block(move || -> anyhow::Result<_> {
+            let login_credentials = CredentialsDTO {
+                username: credentials.username.clone(),
+                password: credentials.password.clone(),
+            };
+            user_service::create_user(&mut conn, credentials)?.expect("username is unique");
+            Ok(user_service::login(&mut conn, login_credentials)?.expect("credentials are valid"))
+        })
+            .await
+            .expect("running blocking task")
+            .expect("creating a user session");
+
+        let config = Config { image_formats: vec![ImageFormat::Jpeg, ImageFormat::Gif, ImageFormat::Webp], ..Config::default() };
+        let app = test::init_service(
+            App::new()
+            .wrap(from_fn(authenticate))
+            .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(config))
+            .service(set_avatar)
+        ).await;
+
+        let upload = |data: &[u8]| test::TestRequest::put()
+            .uri("/api/v1/users/me/avatar")
+            .insert_header(("Authorization", format!("Bearer {}", session.token)))
+            .insert_header(("Content-Type", "multipart/form-data; boundary=my_boundary"))
+            .set_payload(create_avatar_multipart("image", data))
+            .to_request();
+
+        let images = [
+            (image::ImageFormat::Jpeg, "image/jpeg"),
+            (image::ImageFormat::Gif, "image/gif"),
+            (image::ImageFormat::WebP, "image/webp"),
+        ];
+        let mut avatars = Vec::new();
+        for (format, mime_type) in images {
+            let resp = test::call_service(&app, upload(&encode_image(format))).await;
+            assert!(resp.status().is_success());
+            let profile: ProfileDTO = test::read_body_json(resp).await;
+            let avatar = profile.avatar.expect("avatar is set");
+            assert_eq!(image_service::image_meta(&avatar).expect("reading image metadata").unwrap().mime_type, mime_type);
+            avatars.push(avatar);
+        }
+
+        // PNG is left out of the accepted formats, RIFF files other than WebP are not images,
+        // and files that only start like an image can not be decoded
+        let png = fs::read("images/placeholder_avatar").expect("reading placeholder avatar");
+        for data in [&png[..], b"RIFF\x24\x00\x00\x00WAVEfmt ", b"\xFF\xD8\xFF\xE0\x00\x10JFIF\x00\x01"] {
+            let resp = test::call_service(&app, upload(data)).await;
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        let last_avatar = avatars.last().expect("avatar was uploaded");
+        fs::remove_file(format!("images/{last_avatar}")).expect("removing avatar");
+        fs::remove_file(format!("images/{last_avatar}.meta")).expect("removing avatar metadata");
+    }
+}
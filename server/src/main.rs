@@ -1,7 +1,9 @@
 use std::{env, io};
 use actix_cors::Cors;
-use actix_web::{middleware::Logger, web::Data, App, HttpServer};
-use db::establish_connection_pool;
+use actix_web::http::header;
+use actix_web::{middleware::{from_fn, Logger}, web::Data, App, HttpServer};
+use config::Config;
+use db::{establish_connection_pool, run_migrations};
 use env_logger::Env;
 use log::{log, Level};
 
@@ -10,6 +12,9 @@ pub mod schema;
 pub mod db;
 pub mod service;
 pub mod handlers;
+pub mod auth;
+pub mod config;
+pub mod publisher;
 
 const LOGGER_FORMAT: &str = "[%t] %a %s UA:%{User-Agent}i CT:%{Content-Type}i %Dms";
 
@@ -33,6 +38,14 @@ async fn main() -> std::io::Result<()> {
     }
     let db_url = db_url.unwrap();
 
+    let config = Config::from_env();
+    if config.is_err() {
+        let err_msg = unroll_anyhow_result(config.err().unwrap());
+        log!(Level::Error, "Reading config: {}", err_msg);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Error reading config: {err_msg}")));
+    }
+    let config = config.unwrap();
+
     let connection_pool = establish_connection_pool(db_url);
     if connection_pool.is_err() {
         let err_msg = unroll_anyhow_result(connection_pool.err().unwrap());
@@ -42,16 +55,60 @@ async fn main() -> std::io::Result<()> {
     let connection_pool = connection_pool.unwrap();
     log!(Level::Info, "DB connection pool created");
 
+    let migrations = run_migrations(&connection_pool);
+    if migrations.is_err() {
+        let err_msg = unroll_anyhow_result(migrations.err().unwrap());
+        log!(Level::Error, "Running DB migrations: {}", err_msg);
+        return Err(io::Error::other(format!("Error running DB migrations: {err_msg}")));
+    }
+    for version in migrations.unwrap() {
+        log!(Level::Info, "Applied DB migration {}", version);
+    }
+
+    // "migrate" only brings the schema up to date without starting the server
+    if env::args().nth(1).as_deref() == Some("migrate") {
+        return Ok(());
+    }
+
+    actix_web::rt::spawn(publisher::run(connection_pool.clone(), config.publish_interval));
+
     HttpServer::new(move || {
         App::new()
+            .wrap(from_fn(auth::authenticate))
             .wrap(Logger::new(LOGGER_FORMAT))
             .wrap(Cors::default()
                 .allow_any_origin()
-                .allowed_methods(vec!["GET", "POST", "OPTIONS"]))
+                .allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"])
+                .allowed_headers(vec![header::AUTHORIZATION, header::CONTENT_TYPE]))
             .app_data(Data::new(connection_pool.clone()))
+            .app_data(Data::new(config.clone()))
             .service(handlers::blogpost_handler::create_blogpost)
             .service(handlers::blogpost_handler::get_feed)
+            .service(handlers::blogpost_handler::get_own_blogposts)
+            .service(handlers::blogpost_handler::get_author_feed)
+            .service(handlers::blogpost_handler::get_blogpost_by_slug)
+            .service(handlers::blogpost_handler::get_blogpost)
+            .service(handlers::blogpost_handler::update_blogpost)
+            .service(handlers::blogpost_handler::delete_blogpost)
             .service(handlers::image_handler::get_image)
+            .service(handlers::image_handler::get_image_variant)
+            .service(handlers::user_handler::register)
+            .service(handlers::user_handler::login)
+            .service(handlers::user_handler::logout)
+            .service(handlers::user_handler::me)
+            .service(handlers::user_handler::get_profile)
+            .service(handlers::user_handler::update_profile)
+            .service(handlers::user_handler::set_avatar)
+            .service(handlers::comment_handler::create_comment)
+            .service(handlers::comment_handler::get_comments)
+            .service(handlers::comment_handler::delete_comment)
+            .service(handlers::reaction_handler::add_reaction)
+            .service(handlers::reaction_handler::remove_reaction)
+            .service(handlers::tag_handler::get_tags)
+            .service(handlers::search_handler::search)
+            .service(handlers::syndication_handler::rss_feed)
+            .service(handlers::syndication_handler::atom_feed)
+            .service(handlers::syndication_handler::json_feed)
     })
     .bind(("0.0.0.0", 8080))?
     .run()
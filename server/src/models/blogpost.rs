@@ -1,53 +1,250 @@
-use chrono::{NaiveDate, Utc};
-use diesel::prelude::{Insertable, Queryable};
+use chrono::{DateTime, Utc};
+use diesel::{
+    define_sql_function,
+    dsl::sql,
+    expression::SqlLiteral,
+    prelude::{AsChangeset, Insertable, Queryable, Selectable},
+    sql_types::{Array, BigInt, Jsonb, Nullable, Text, Varchar},
+    NullableExpressionMethods,
+};
 use serde::{Deserialize, Serialize};
-use crate::schema::BlogPostTable;
-use super::CreateBlogPostDTO;
+use crate::auth::AuthenticatedUser;
+use crate::schema::{BlogPostTable, UserTable};
+use super::{normalize_title, CreateBlogPostDTO, STATUS_PUBLISHED, Excerpt, ReactionCounts, RenderedHtml, UpdateBlogPostDTO};
 
-#[derive(Queryable, Insertable, Debug, Serialize, Deserialize)]
+define_sql_function!(fn coalesce(x: Nullable<Varchar>, y: Nullable<Varchar>) -> Nullable<Varchar>);
+
+// expressions shared by `BlogPost` and `FeedItem`, so a blogpost has the same counts in the feed and on its own
+
+type Avatar = coalesce<diesel::dsl::Nullable<UserTable::avatar>, BlogPostTable::avatar>;
+
+/// avatar of the author's profile, falling back to the copy saved with the blogpost
+fn avatar() -> Avatar {
+    coalesce(UserTable::avatar.nullable(), BlogPostTable::avatar)
+}
+
+/// comments that were not deleted, including replies
+const COMMENT_COUNT_SQL: &str =
+    "(SELECT COUNT(*) FROM comment WHERE comment.post_id = blogpost.id AND comment.deleted_at IS NULL)";
+
+/// object with the number of reactions of every kind
+const REACTION_COUNTS_SQL: &str =
+    "(SELECT COALESCE(jsonb_object_agg(counts.kind, counts.count), '{}') \
+    FROM (SELECT kind, COUNT(*) AS count FROM reaction WHERE reaction.post_id = blogpost.id GROUP BY kind) counts)";
+
+/// names of the tags, ordered alphabetically
+const TAGS_SQL: &str =
+    "ARRAY(SELECT tag.name FROM blogpost_tag JOIN tag ON tag.id = blogpost_tag.tag_id \
+    WHERE blogpost_tag.post_id = blogpost.id ORDER BY tag.name)";
+
+/// has to be selected from `blogpost` left joined with `users`, see `BlogPost::as_select`
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
 #[diesel(table_name = BlogPostTable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct BlogPost {
     pub id: i32,
 
-    /// not longer than 2000
+    /// None for posts without a title, not longer than 200 characters
+    pub title: Option<String>,
+
+    /// unique, generated from the title when the post gets one
+    /// and kept when the title changes so the permalinks keep working
+    pub slug: Option<String>,
+
+    /// CommonMark source, not longer than 100000
     pub text: String,
 
+    /// text rendered to sanitized HTML
+    #[diesel(select_expression = BlogPostTable::text)]
+    #[diesel(select_expression_type = BlogPostTable::text)]
+    #[diesel(deserialize_as = String)]
+    pub html: RenderedHtml,
+
+    /// beginning of the text without the markup
+    #[diesel(select_expression = BlogPostTable::text)]
+    #[diesel(select_expression_type = BlogPostTable::text)]
+    #[diesel(deserialize_as = String)]
+    pub excerpt: Excerpt,
+
     /// not longer than 128
     pub username: String,
 
-    #[diesel(column_name = dateofpublication)]
-    pub date_of_publication: NaiveDate,
+    /// serialized as RFC 3339
+    /// time of the creation for blogposts that were not published yet
+    pub published_at: DateTime<Utc>,
+
+    /// one of POST_STATUSES, only published blogposts are visible to users other than the author
+    pub status: String,
+
+    /// when a scheduled blogpost gets published, serialized as RFC 3339
+    pub publish_at: Option<DateTime<Utc>>,
+
+    /// avatar uuid, taken from the current profile of the author,
+    /// posts created before profiles existed fall back to their own copy
+    #[diesel(select_expression = avatar())]
+    #[diesel(select_expression_type = Avatar)]
+    pub avatar: Option<String>,
+
+    /// post image uuid
+    #[diesel(column_name = postimage)]
+    pub post_image: Option<String>,
+
+    /// time of the last edit, None if the post was never edited
+    pub edited_at: Option<DateTime<Utc>>,
+
+    /// author, None for posts created before user accounts existed
+    pub user_id: Option<i32>,
+
+    /// number of comments on the blogpost, including replies but not deleted comments
+    #[diesel(select_expression = sql::<BigInt>(COMMENT_COUNT_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<BigInt>)]
+    pub comment_count: i64,
+
+    /// number of reactions of every kind
+    #[diesel(select_expression = sql::<Jsonb>(REACTION_COUNTS_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<Jsonb>)]
+    #[diesel(deserialize_as = serde_json::Value)]
+    pub reactions: ReactionCounts,
+
+    /// names of the tags, ordered alphabetically
+    #[diesel(select_expression = sql::<Array<Text>>(TAGS_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<Array<Text>>)]
+    pub tags: Vec<String>,
+}
+
+/// blogpost as it is listed in the feeds, without the text and the rendered HTML,
+/// has to be selected from `blogpost` left joined with `users` like `BlogPost`
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = BlogPostTable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct FeedItem {
+    pub id: i32,
+
+    /// None for posts without a title
+    pub title: Option<String>,
+
+    pub slug: Option<String>,
+
+    /// beginning of the text without the markup
+    #[diesel(select_expression = BlogPostTable::text)]
+    #[diesel(select_expression_type = BlogPostTable::text)]
+    #[diesel(deserialize_as = String)]
+    pub excerpt: Excerpt,
+
+    pub username: String,
+
+    /// serialized as RFC 3339
+    pub published_at: DateTime<Utc>,
 
-    /// avatar uuid
+    /// one of POST_STATUSES
+    pub status: String,
+
+    /// serialized as RFC 3339
+    pub publish_at: Option<DateTime<Utc>>,
+
+    /// avatar uuid, taken from the current profile of the author
+    #[diesel(select_expression = avatar())]
+    #[diesel(select_expression_type = Avatar)]
     pub avatar: Option<String>,
 
     /// post image uuid
     #[diesel(column_name = postimage)]
     pub post_image: Option<String>,
+
+    pub edited_at: Option<DateTime<Utc>>,
+
+    pub user_id: Option<i32>,
+
+    /// number of comments on the blogpost, including replies but not deleted comments
+    #[diesel(select_expression = sql::<BigInt>(COMMENT_COUNT_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<BigInt>)]
+    pub comment_count: i64,
+
+    /// number of reactions of every kind
+    #[diesel(select_expression = sql::<Jsonb>(REACTION_COUNTS_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<Jsonb>)]
+    #[diesel(deserialize_as = serde_json::Value)]
+    pub reactions: ReactionCounts,
+
+    /// names of the tags, ordered alphabetically
+    #[diesel(select_expression = sql::<Array<Text>>(TAGS_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<Array<Text>>)]
+    pub tags: Vec<String>,
+}
+
+impl BlogPost {
+    /// published blogposts are visible to everyone, the others only to their author
+    pub fn is_visible_to(&self, user_id: Option<i32>) -> bool {
+        self.status == STATUS_PUBLISHED || (user_id.is_some() && self.user_id == user_id)
+    }
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = BlogPostTable)]
 /// used for inserting a new post
 pub struct NewPost {
+    pub title: Option<String>,
+    /// set once the title is known to be free
+    pub slug: Option<String>,
     pub text: String,
     pub username: String,
-    #[diesel(column_name = dateofpublication)]
-    pub date_of_publication: NaiveDate,
-    pub avatar: Option<String>,
+    pub published_at: DateTime<Utc>,
     #[diesel(column_name = postimage)]
     pub post_image: Option<String>,
+    pub user_id: i32,
+    pub status: String,
+    pub publish_at: Option<DateTime<Utc>>,
 }
 
 impl NewPost {
-    pub fn from_create_blog_post_dto(dto: CreateBlogPostDTO, avatar: Option<String>, post_image: Option<String>) -> Self {
-        let today = Utc::now().naive_utc();
+    pub fn from_create_blog_post_dto(
+        dto: CreateBlogPostDTO,
+        author: &AuthenticatedUser,
+        post_image: Option<String>) -> Self {
         NewPost {
+            title: dto.title.as_deref().and_then(normalize_title),
+            slug: None,
+            text: dto.text,
+            username: author.username.clone(),
+            published_at: Utc::now(),
+            post_image,
+            user_id: author.id,
+            status: dto.status.unwrap_or_else(|| STATUS_PUBLISHED.to_string()),
+            publish_at: dto.publish_at,
+        }
+    }
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = BlogPostTable)]
+/// used for updating an existing post, fields that are None are left unchanged
+pub struct PostChanges {
+    /// Some(None) removes the title
+    pub title: Option<Option<String>>,
+    /// only set for posts that did not have a slug yet
+    pub slug: Option<String>,
+    pub text: Option<String>,
+    #[diesel(column_name = postimage)]
+    pub post_image: Option<String>,
+    pub edited_at: DateTime<Utc>,
+    pub status: Option<String>,
+    /// changes together with the status, Some(None) for statuses other than scheduled
+    pub publish_at: Option<Option<DateTime<Utc>>>,
+    /// only set when the blogpost gets published
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl PostChanges {
+    pub fn from_update_blog_post_dto(dto: UpdateBlogPostDTO, post_image: Option<String>) -> Self {
+        PostChanges {
+            title: dto.title.map(|title| normalize_title(&title)),
+            slug: None,
             text: dto.text,
-            username: dto.username,
-            date_of_publication: today.into(),
-            avatar,
-            post_image
+            post_image,
+            edited_at: Utc::now(),
+            publish_at: dto.status.as_ref().map(|_| dto.publish_at),
+            status: dto.status,
+            published_at: None,
         }
     }
 }
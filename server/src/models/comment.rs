@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use diesel::{
+    dsl::sql,
+    expression::SqlLiteral,
+    prelude::{Insertable, Queryable, Selectable},
+    sql_types::{Integer, Nullable, Varchar},
+};
+use serde::{Deserialize, Serialize};
+use crate::auth::AuthenticatedUser;
+use crate::schema::CommentTable;
+use super::CreateCommentDTO;
+
+/// author of the comment, NULL for tombstones
+const AUTHOR_ID_SQL: &str = "CASE WHEN comment.deleted_at IS NULL THEN comment.user_id END";
+
+/// username of the author, NULL for tombstones
+const AUTHOR_USERNAME_SQL: &str = "CASE WHEN comment.deleted_at IS NULL THEN users.username END";
+
+/// has to be selected from `comment` joined with `users`, see `Comment::as_select`
+#[derive(Queryable, Selectable, Debug, Serialize, Deserialize)]
+#[diesel(table_name = CommentTable)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Comment {
+    pub id: i32,
+
+    pub post_id: i32,
+
+    /// author, None if the comment was deleted
+    #[diesel(select_expression = sql::<Nullable<Integer>>(AUTHOR_ID_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<Nullable<Integer>>)]
+    pub user_id: Option<i32>,
+
+    /// username of the author, None if the comment was deleted
+    #[diesel(select_expression = sql::<Nullable<Varchar>>(AUTHOR_USERNAME_SQL))]
+    #[diesel(select_expression_type = SqlLiteral<Nullable<Varchar>>)]
+    pub username: Option<String>,
+
+    /// not longer than 1000, empty if the comment was deleted
+    pub text: String,
+
+    /// serialized as RFC 3339
+    pub created_at: DateTime<Utc>,
+
+    /// comment this one replies to, None for top level comments
+    pub parent_id: Option<i32>,
+
+    /// top level comments have depth 0
+    pub depth: i32,
+
+    /// deleted comments that have replies are kept as tombstones
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = CommentTable)]
+/// used for inserting a new comment
+pub struct NewComment {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+    pub parent_id: Option<i32>,
+    pub root_id: Option<i32>,
+    pub depth: i32,
+}
+
+impl NewComment {
+    /// `root_id` and `depth` are taken from the parent comment if the dto has one
+    pub fn from_create_comment_dto(
+        dto: CreateCommentDTO,
+        post_id: i32,
+        author: &AuthenticatedUser,
+        root_id: Option<i32>,
+        depth: i32) -> Self {
+        NewComment {
+            post_id,
+            user_id: author.id,
+            text: dto.text.trim().to_string(),
+            created_at: Utc::now(),
+            parent_id: dto.parent_id,
+            root_id,
+            depth,
+        }
+    }
+}
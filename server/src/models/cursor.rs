@@ -0,0 +1,77 @@
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+
+/// position of a blogpost in the feed, used for keyset pagination
+///
+/// clients receive it as an opaque string and send it back unchanged to get the next page
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedCursor {
+    pub published_at: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl FeedCursor {
+    pub fn encode(&self) -> String {
+        // microseconds match the precision of the db timestamp
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.published_at.timestamp_micros(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .context("decoding cursor")?;
+        let cursor = String::from_utf8(bytes)
+            .context("reading cursor as string")?;
+
+        let (published_at, id) = cursor
+            .split_once('|')
+            .ok_or(anyhow!("malformed cursor: {cursor}"))?;
+        let published_at = published_at
+            .parse()
+            .context("parsing cursor timestamp")?;
+
+        Ok(FeedCursor {
+            published_at: DateTime::from_timestamp_micros(published_at)
+                .ok_or(anyhow!("cursor timestamp out of range: {published_at}"))?,
+            id: id.parse().context("parsing cursor id")?,
+        })
+    }
+}
+
+/// position of a search result, results are ordered by the rank and then by the id
+///
+/// clients receive it as an opaque string and send it back unchanged to get the next page
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchCursor {
+    pub rank: f32,
+    pub id: i32,
+}
+
+impl SearchCursor {
+    pub fn encode(&self) -> String {
+        // f32 display is the shortest representation that parses back to the same value
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.rank, self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .context("decoding cursor")?;
+        let cursor = String::from_utf8(bytes)
+            .context("reading cursor as string")?;
+
+        let (rank, id) = cursor
+            .split_once('|')
+            .ok_or(anyhow!("malformed cursor: {cursor}"))?;
+        let rank: f32 = rank
+            .parse()
+            .context("parsing cursor rank")?;
+        if !rank.is_finite() { return Err(anyhow!("cursor rank is not finite: {rank}")); }
+
+        Ok(SearchCursor {
+            rank,
+            id: id.parse().context("parsing cursor id")?,
+        })
+    }
+}
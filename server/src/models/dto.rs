@@ -1,24 +1,220 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::BlogPost;
+use super::{Comment, FeedCursor, FeedItem, SearchCursor, User};
 
-pub const MAX_TEXT_SIZE: usize = 2000;
+pub const MAX_TEXT_SIZE: usize = 100_000;
+/// in characters
+pub const MAX_TITLE_SIZE: usize = 200;
 pub const MAX_USERNAME_SIZE: usize = 128;
+pub const MIN_PASSWORD_SIZE: usize = 8;
+pub const MAX_PASSWORD_SIZE: usize = 128;
+/// usernames that would be shadowed by the /api/v1/users/me routes
+pub const RESERVED_USERNAMES: [&str; 1] = ["me"];
+pub const MAX_DISPLAY_NAME_SIZE: usize = 128;
+pub const MAX_BIO_SIZE: usize = 500;
+/// in characters
+pub const MAX_COMMENT_SIZE: usize = 1000;
+pub const MAX_TAG_SIZE: usize = 32;
+pub const MAX_TAGS: usize = 10;
+pub const MAX_SEARCH_QUERY_SIZE: usize = 256;
+/// blogposts are visible to everyone only once they are published
+pub const STATUS_DRAFT: &str = "draft";
+/// published by the server once publish_at passes
+pub const STATUS_SCHEDULED: &str = "scheduled";
+pub const STATUS_PUBLISHED: &str = "published";
+pub const POST_STATUSES: [&str; 3] = [STATUS_DRAFT, STATUS_SCHEDULED, STATUS_PUBLISHED];
+/// kinds of reactions users can react to a blogpost with
+pub const REACTION_KINDS: [&str; 6] = ["like", "love", "laugh", "wow", "sad", "angry"];
 pub const MAX_IMAGE_SIZE: usize = 2 * 1024 * 1024;
+/// number of blogposts in a feed page when the limit is not provided
+pub const DEFAULT_PAGE_SIZE: u32 = 5;
+pub const MAX_PAGE_SIZE: u32 = 50;
+pub const DEFAULT_COMMENTS_PAGE_SIZE: u32 = 20;
 
-/// text max len - 2000b
-/// username max len - 128b
-/// date max len - 10b
-/// avatar max size - 2mb
+/// text max len - 100000b
+/// title is optional, max len - 200 characters
 /// post image max size - 2mb
+/// tags max count - 10, tag max len - 32b
+/// status is one of POST_STATUSES, published by default, publish_at is required only for scheduled blogposts
+/// author is the authenticated user, avatar is taken from their profile
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateBlogPostDTO {
     pub text: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    /// serialized as RFC 3339
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+impl CreateBlogPostDTO {
+    pub fn fits(&self) -> bool {
+        self.text.len() <= MAX_TEXT_SIZE && title_fits(&self.title)
+    }
+
+    pub fn has_valid_status(&self) -> bool {
+        is_valid_status(self.status.as_deref().unwrap_or(STATUS_PUBLISHED), self.publish_at)
+    }
+
+    /// normalizes and deduplicates the tags, None if any of them is invalid or there are too many
+    pub fn normalized_tags(&self) -> Option<Vec<String>> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in &self.tags {
+            let tag = normalize_tag(tag)?;
+            if !tags.contains(&tag) { tags.push(tag); }
+        }
+        if tags.len() > MAX_TAGS { return None; }
+        Some(tags)
+    }
+}
+
+/// collapses the whitespace in the title, None if nothing is left
+pub fn normalize_title(title: &str) -> Option<String> {
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() { None } else { Some(title) }
+}
+
+/// publish_at has to be provided for scheduled blogposts and only for them
+pub fn is_valid_status(status: &str, publish_at: Option<DateTime<Utc>>) -> bool {
+    POST_STATUSES.contains(&status) && (status == STATUS_SCHEDULED) == publish_at.is_some()
+}
+
+fn title_fits(title: &Option<String>) -> bool {
+    title.as_deref().unwrap_or("").chars().count() <= MAX_TITLE_SIZE
+}
+
+/// trims the tag, removes the leading '#' and lowercases it
+/// None if the result is empty, longer than MAX_TAG_SIZE, or has characters other than letters, digits, '_' and '-'
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim();
+    let tag = tag.strip_prefix('#').unwrap_or(tag).to_lowercase();
+    let is_valid = !tag.is_empty()
+        && tag.len() <= MAX_TAG_SIZE
+        && tag.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if is_valid { Some(tag) } else { None }
+}
+
+/// text max len - 100000b
+/// title max len - 200 characters, an empty title removes it
+/// post image max size - 2mb
+/// publish_at can only be provided together with the scheduled status
+/// fields that are not provided are left unchanged
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateBlogPostDTO {
+    pub text: Option<String>,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    /// serialized as RFC 3339
+    #[serde(default)]
+    pub publish_at: Option<DateTime<Utc>>,
+}
+
+impl UpdateBlogPostDTO {
+    pub fn fits(&self) -> bool {
+        self.text.as_deref().unwrap_or("").len() <= MAX_TEXT_SIZE && title_fits(&self.title)
+    }
+
+    pub fn has_valid_status(&self) -> bool {
+        match &self.status {
+            Some(status) => is_valid_status(status, self.publish_at),
+            None => self.publish_at.is_none(),
+        }
+    }
+}
+
+/// used both for registration and login
+/// username max len - 128b, only ascii letters, digits, '_', '-' and '.', none of RESERVED_USERNAMES
+/// password len - 8b to 128b
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CredentialsDTO {
     pub username: String,
-    /// avatar image url
+    pub password: String,
+}
+
+impl CredentialsDTO {
+    pub fn is_valid(&self) -> bool {
+        !self.username.is_empty()
+            && self.username.len() <= MAX_USERNAME_SIZE
+            && self.username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+            && !RESERVED_USERNAMES.contains(&self.username.as_str())
+            && self.password.len() >= MIN_PASSWORD_SIZE
+            && self.password.len() <= MAX_PASSWORD_SIZE
+    }
+}
+
+/// token has to be sent in the Authorization header as "Bearer <token>"
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionDTO {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// text max len - 1000b
+/// author is the authenticated user
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCommentDTO {
+    pub text: String,
+    /// comment that is replied to, None for top level comments
+    #[serde(default)]
+    pub parent_id: Option<i32>,
+}
+
+impl CreateCommentDTO {
+    /// the text is trimmed before it is saved, so it is checked trimmed as well
+    pub fn is_valid(&self) -> bool {
+        let text = self.text.trim();
+        !text.is_empty() && text.chars().count() <= MAX_COMMENT_SIZE
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserDTO {
+    pub id: i32,
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileDTO {
+    pub username: String,
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+    /// avatar uuid
     pub avatar: Option<String>,
 }
 
+impl From<User> for ProfileDTO {
+    fn from(user: User) -> Self {
+        ProfileDTO {
+            username: user.username,
+            display_name: user.display_name,
+            bio: user.bio,
+            avatar: user.avatar,
+        }
+    }
+}
+
+/// display name max len - 128b
+/// bio max len - 500b
+/// replaces both fields, missing ones are cleared
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateProfileDTO {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+}
+
+impl UpdateProfileDTO {
+    pub fn is_valid(&self) -> bool {
+        self.display_name.as_deref().unwrap_or("").len() <= MAX_DISPLAY_NAME_SIZE
+            && self.bio.as_deref().unwrap_or("").len() <= MAX_BIO_SIZE
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct GenericErrorMessageDTO {
     pub error: String,
@@ -30,15 +226,219 @@ impl GenericErrorMessageDTO {
     }
 }
 
+/// either page or cursor has to be provided
+/// limit max - 50
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    pub page: Option<u32>,
+    /// next_cursor from the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    /// only blogposts with the tag
+    pub tag: Option<String>,
+}
+
+/// only blogposts with the status, blogposts of every status when missing
+#[derive(Debug, Deserialize)]
+pub struct StatusQuery {
+    pub status: Option<String>,
+}
+
+/// `w` is the width the image is shown at, the narrowest variant that is at least as wide is served
+#[derive(Debug, Deserialize)]
+pub struct ImageQuery {
+    pub w: Option<u32>,
+}
+
+/// filters of the RSS and Atom feeds, the feeds always have the newest blogposts
+#[derive(Debug, Deserialize)]
+pub struct SyndicationQuery {
+    /// only blogposts with the tag
+    pub tag: Option<String>,
+    /// only blogposts of the author
+    pub author: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FeedDTO {
-    pub blogposts: Vec<BlogPost>
+    /// without the text and the HTML, those are only returned for a single blogpost
+    pub blogposts: Vec<FeedItem>,
+    /// cursor for the next page, None if there are no more blogposts
+    pub next_cursor: Option<String>,
+    /// None when the page was requested with a cursor
+    pub page: Option<u32>,
+    pub page_size: u32,
+    /// number of blogposts in the whole feed
+    pub total_count: i64,
+    pub has_next: bool,
+    pub has_previous: bool,
 }
 
 impl FeedDTO {
-    pub fn new(blogposts: Vec<BlogPost>) -> Self {
+    pub fn new(
+        blogposts: Vec<FeedItem>,
+        next_cursor: Option<FeedCursor>,
+        page: Option<u32>,
+        page_size: u32,
+        total_count: i64) -> Self {
         FeedDTO {
-            blogposts
+            blogposts,
+            has_next: next_cursor.is_some(),
+            next_cursor: next_cursor.map(|cursor| cursor.encode()),
+            // pages start from 1 and a cursor always points after at least one blogpost
+            has_previous: page != Some(1),
+            page,
+            page_size,
+            total_count,
+        }
+    }
+}
+
+/// version of the JSON Feed format written at /feed.json
+pub const JSON_FEED_VERSION: &str = "https://jsonfeed.org/version/1.1";
+
+/// top level object of a JSON Feed 1.1 document, optional fields are left out when missing
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonFeedDTO {
+    pub version: String,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub home_page_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feed_url: Option<String>,
+    pub items: Vec<JsonFeedItemDTO>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonFeedItemDTO {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// excerpt of the text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    pub content_html: String,
+    /// CommonMark source of the blogpost
+    pub content_text: String,
+    /// url of the post image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// RFC 3339
+    pub date_published: String,
+    /// RFC 3339, only for edited blogposts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_modified: Option<String>,
+    pub authors: Vec<JsonFeedAuthorDTO>,
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonFeedAuthorDTO {
+    pub name: String,
+    /// url of the avatar image
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+}
+
+/// page defaults to 1
+/// limit max - 50
+#[derive(Debug, Deserialize)]
+pub struct CommentsQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+/// comments of a blogpost, pages consist of top level comments ordered from the oldest to the newest,
+/// each followed by all of its replies
+///
+/// replies come right after the comment they reply to, use `parent_id` and `depth` to rebuild the tree
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentsDTO {
+    pub comments: Vec<Comment>,
+    pub page: u32,
+    pub page_size: u32,
+    /// number of top level comments on the blogpost
+    pub total_count: i64,
+    pub has_next: bool,
+    pub has_previous: bool,
+}
+
+impl CommentsDTO {
+    pub fn new(comments: Vec<Comment>, page: u32, page_size: u32, total_count: i64) -> Self {
+        CommentsDTO {
+            comments,
+            has_next: (page as i64) * (page_size as i64) < total_count,
+            has_previous: page > 1,
+            page,
+            page_size,
+            total_count,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagDTO {
+    pub name: String,
+    /// number of blogposts with the tag
+    pub count: i64,
+}
+
+/// query max len - 256b
+/// either page or cursor has to be provided
+/// limit max - 50
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    /// words to search for, supports quoted phrases, "or" and "-" for excluding words
+    pub q: String,
+    pub page: Option<u32>,
+    /// next_cursor from the previous page
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResultDTO {
+    pub blogpost: FeedItem,
+    /// higher is a better match
+    pub rank: f32,
+    /// html escaped parts of the text with the matched words wrapped in <mark></mark>
+    pub snippet: String,
+}
+
+/// results are ordered from the best to the worst match
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchDTO {
+    pub results: Vec<SearchResultDTO>,
+    /// cursor for the next page, None if there are no more results
+    pub next_cursor: Option<String>,
+    /// None when the page was requested with a cursor
+    pub page: Option<u32>,
+    pub page_size: u32,
+    /// number of all matching blogposts
+    pub total_count: i64,
+    pub has_next: bool,
+    pub has_previous: bool,
+}
+
+impl SearchDTO {
+    pub fn new(
+        results: Vec<SearchResultDTO>,
+        next_cursor: Option<SearchCursor>,
+        page: Option<u32>,
+        page_size: u32,
+        total_count: i64) -> Self {
+        SearchDTO {
+            results,
+            has_next: next_cursor.is_some(),
+            next_cursor: next_cursor.map(|cursor| cursor.encode()),
+            // pages start from 1 and a cursor always points after at least one result
+            has_previous: page != Some(1),
+            page,
+            page_size,
+            total_count,
         }
     }
 }
@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+const PNG_MAGIC_BYTES: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+const JPEG_MAGIC_BYTES: [u8; 3] = [0xFF, 0xD8, 0xFF];
+const GIF87_MAGIC_BYTES: &[u8; 6] = b"GIF87a";
+const GIF89_MAGIC_BYTES: &[u8; 6] = b"GIF89a";
+/// WebP is a RIFF container, the 4 bytes between the two tags hold the file size
+const RIFF_TAG: &[u8; 4] = b"RIFF";
+const WEBP_TAG: &[u8; 4] = b"WEBP";
+/// number of bytes from the start of the file needed to recognize all formats
+pub const IMAGE_SNIFF_SIZE: usize = 12;
+
+/// formats of the images that can be uploaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageFormat {
+    pub const ALL: [ImageFormat; 4] = [ImageFormat::Png, ImageFormat::Jpeg, ImageFormat::Gif, ImageFormat::Webp];
+
+    /// recognizes the format from the first IMAGE_SNIFF_SIZE bytes of the file, None if it is none of the formats
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&PNG_MAGIC_BYTES) { return Some(ImageFormat::Png); }
+        if bytes.starts_with(&JPEG_MAGIC_BYTES) { return Some(ImageFormat::Jpeg); }
+        if bytes.starts_with(GIF87_MAGIC_BYTES) || bytes.starts_with(GIF89_MAGIC_BYTES) { return Some(ImageFormat::Gif); }
+        if bytes.len() >= IMAGE_SNIFF_SIZE && bytes.starts_with(RIFF_TAG) && &bytes[8..12] == WEBP_TAG {
+            return Some(ImageFormat::Webp);
+        }
+        None
+    }
+
+    /// name used in the IMAGE_FORMATS setting, "jpg" is accepted as well as "jpeg"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "png" => Some(ImageFormat::Png),
+            "jpeg" | "jpg" => Some(ImageFormat::Jpeg),
+            "gif" => Some(ImageFormat::Gif),
+            "webp" => Some(ImageFormat::Webp),
+            _ => None,
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "image/png",
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Webp => "image/webp",
+        }
+    }
+}
+
+/// smaller copy of an image generated on upload, images narrower than the variant do not get it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageVariant {
+    /// used in the url and in the file name
+    pub name: &'static str,
+    /// width in pixels, the height keeps the aspect ratio
+    pub width: u32,
+}
+
+pub const AVATAR_VARIANT: ImageVariant = ImageVariant { name: "avatar", width: 64 };
+pub const SMALL_VARIANT: ImageVariant = ImageVariant { name: "small", width: 480 };
+pub const LARGE_VARIANT: ImageVariant = ImageVariant { name: "large", width: 1080 };
+/// all variants from the narrowest
+pub const IMAGE_VARIANTS: [ImageVariant; 3] = [AVATAR_VARIANT, SMALL_VARIANT, LARGE_VARIANT];
+pub const AVATAR_VARIANTS: [ImageVariant; 1] = [AVATAR_VARIANT];
+pub const POST_IMAGE_VARIANTS: [ImageVariant; 2] = [SMALL_VARIANT, LARGE_VARIANT];
+
+impl ImageVariant {
+    pub fn from_name(name: &str) -> Option<Self> {
+        IMAGE_VARIANTS.into_iter().find(|variant| variant.name == name)
+    }
+}
+
+/// stored next to every image, read when the image is served
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageMeta {
+    pub mime_type: String,
+    /// hex encoded sha256 of the image, images never change so it is used as a strong ETag
+    pub etag: String,
+}
+
+impl ImageMeta {
+    pub fn new(format: ImageFormat, etag: String) -> Self {
+        ImageMeta { mime_type: format.mime_type().to_string(), etag }
+    }
+}
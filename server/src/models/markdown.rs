@@ -0,0 +1,77 @@
+use std::sync::LazyLock;
+use pulldown_cmark::{html::push_html, Event, Options, Parser, TagEnd};
+use serde::{Deserialize, Serialize};
+
+/// removes scripts, event handlers and unsafe urls from the rendered HTML,
+/// links get rel="noopener noreferrer nofollow" so posts can not pass ranking or the opener to other sites
+static SANITIZER: LazyLock<ammonia::Builder<'static>> = LazyLock::new(|| {
+    let mut sanitizer = ammonia::Builder::default();
+    sanitizer.link_rel(Some("noopener noreferrer nofollow"));
+    sanitizer
+});
+
+/// length of the excerpts in characters, longer texts are cut at the last whole word
+pub const EXCERPT_SIZE: usize = 280;
+
+/// CommonMark text rendered to HTML that is safe to insert into a page
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RenderedHtml(pub String);
+
+impl RenderedHtml {
+    pub fn render(markdown: &str) -> Self {
+        let mut html = String::with_capacity(markdown.len() * 3 / 2);
+        push_html(&mut html, Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH));
+        RenderedHtml(SANITIZER.clean(&html).to_string())
+    }
+}
+
+/// the text is stored as CommonMark source and rendered when the blogpost is loaded
+impl From<String> for RenderedHtml {
+    fn from(markdown: String) -> Self {
+        RenderedHtml::render(&markdown)
+    }
+}
+
+/// beginning of the CommonMark text as plain text, used to preview long posts
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Excerpt(pub String);
+
+impl Excerpt {
+    pub fn from_markdown(markdown: &str) -> Self {
+        // markup and raw HTML are dropped, blocks and line breaks become spaces
+        let mut text = String::new();
+        for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+            match event {
+                Event::Text(part) | Event::Code(part) => text.push_str(&part),
+                Event::SoftBreak | Event::HardBreak | Event::End(TagEnd::Paragraph | TagEnd::Heading(_)
+                    | TagEnd::Item | TagEnd::CodeBlock | TagEnd::BlockQuote(_)) => text.push(' '),
+                _ => {}
+            }
+            // some room for the collapsed whitespace
+            if text.len() > EXCERPT_SIZE * 8 { break; }
+        }
+
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let mut excerpt = String::new();
+        for word in &words {
+            let separator = if excerpt.is_empty() { 0 } else { 1 };
+            // one character is left for the ellipsis
+            if excerpt.chars().count() + separator + word.chars().count() > EXCERPT_SIZE - 1 {
+                excerpt.push('\u{2026}');
+                return Excerpt(excerpt);
+            }
+            if separator == 1 { excerpt.push(' '); }
+            excerpt.push_str(word);
+        }
+
+        Excerpt(excerpt)
+    }
+}
+
+impl From<String> for Excerpt {
+    fn from(markdown: String) -> Self {
+        Excerpt::from_markdown(&markdown)
+    }
+}
@@ -1,5 +1,19 @@
 pub mod blogpost;
+pub mod comment;
+pub mod cursor;
 pub mod dto;
+pub mod image;
+pub mod markdown;
+pub mod reaction;
+pub mod user;
 
-pub use blogpost::{BlogPost, NewPost};
+pub use blogpost::{BlogPost, FeedItem, NewPost, PostChanges};
+pub use comment::{Comment, NewComment};
+pub use cursor::{FeedCursor, SearchCursor};
 pub use dto::*;
+pub use image::{
+    ImageFormat, ImageMeta, ImageVariant, AVATAR_VARIANTS, IMAGE_SNIFF_SIZE, IMAGE_VARIANTS, POST_IMAGE_VARIANTS
+};
+pub use markdown::{Excerpt, RenderedHtml, EXCERPT_SIZE};
+pub use reaction::{NewReaction, ReactionCounts};
+pub use user::{NewSession, NewUser, ProfileChanges, User};
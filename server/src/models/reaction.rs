@@ -0,0 +1,29 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use diesel::prelude::Insertable;
+use serde::{Deserialize, Serialize};
+use crate::schema::ReactionTable;
+
+#[derive(Insertable)]
+#[diesel(table_name = ReactionTable)]
+/// used for inserting a new reaction
+pub struct NewReaction {
+    pub post_id: i32,
+    pub user_id: i32,
+    pub kind: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// number of reactions of every kind, kinds nobody reacted with are left out
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ReactionCounts(pub BTreeMap<String, i64>);
+
+/// counts are aggregated into a json object by the database
+impl TryFrom<serde_json::Value> for ReactionCounts {
+    type Error = serde_json::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value)
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::{AsChangeset, Insertable, Queryable};
+use crate::schema::{SessionTable, UserTable};
+use super::UpdateProfileDTO;
+
+/// never serialized, use UserDTO for responses
+#[derive(Queryable, Debug)]
+#[diesel(table_name = UserTable)]
+pub struct User {
+    pub id: i32,
+
+    /// not longer than 128
+    pub username: String,
+
+    /// argon2 PHC string
+    pub password_hash: String,
+
+    pub created_at: DateTime<Utc>,
+
+    /// not longer than 128
+    pub display_name: Option<String>,
+
+    /// not longer than 500
+    pub bio: Option<String>,
+
+    /// avatar uuid
+    pub avatar: Option<String>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = UserTable)]
+/// used for inserting a new user
+pub struct NewUser {
+    pub username: String,
+    pub password_hash: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = SessionTable)]
+/// used for inserting a new session
+pub struct NewSession {
+    /// sha256 of the token handed out to the user
+    pub token_hash: String,
+    pub user_id: i32,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = UserTable, treat_none_as_null = true)]
+/// used for replacing the profile text, the avatar is replaced separately
+pub struct ProfileChanges {
+    pub display_name: Option<String>,
+    pub bio: Option<String>,
+}
+
+impl ProfileChanges {
+    pub fn from_update_profile_dto(dto: UpdateProfileDTO) -> Self {
+        ProfileChanges {
+            display_name: dto.display_name,
+            bio: dto.bio,
+        }
+    }
+}
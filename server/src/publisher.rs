@@ -0,0 +1,50 @@
+use std::time::Duration;
+use actix_web::{rt::time::interval, web};
+use log::{log, Level};
+use crate::{db::DBPool, service::{blogpost_service, image_service, user_service}};
+
+/// images marked for deletion longer than this are leftovers of a failed removal
+const MARKED_IMAGE_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// publishes the scheduled blogposts whose publish_at has passed, purges the expired sessions
+/// and deletes the images left marked for deletion, checks once every `period`
+pub async fn run(pool: DBPool, period: Duration) {
+    let mut ticks = interval(period);
+    loop {
+        ticks.tick().await;
+
+        match image_service::delete_marked_images(MARKED_IMAGE_AGE).await {
+            Ok(0) => {}
+            Ok(deleted) => log!(Level::Info, "Deleted {} images left marked for deletion", deleted),
+            Err(e) => log!(Level::Error, "Error deleting marked images: {}", crate::unroll_anyhow_result(e)),
+        }
+
+        let conn = pool.get();
+        if let Err(e) = conn {
+            log!(Level::Error, "Error getting a connection from pool: {}", e);
+            continue;
+        }
+        let mut conn = conn.unwrap();
+
+        let res = web::block(move || {
+            let published = blogpost_service::publish_scheduled(&mut conn);
+            let purged = user_service::delete_expired_sessions(&mut conn);
+            (published, purged)
+        }).await;
+        if let Err(e) = res {
+            log!(Level::Error, "Error running the publisher: {}", e);
+            continue;
+        }
+        let (published, purged) = res.unwrap();
+        match published {
+            Ok(0) => {}
+            Ok(published) => log!(Level::Info, "Published {} scheduled blogposts", published),
+            Err(e) => log!(Level::Error, "Error publishing scheduled blogposts: {}", crate::unroll_anyhow_result(e)),
+        }
+        match purged {
+            Ok(0) => {}
+            Ok(purged) => log!(Level::Info, "Deleted {} expired sessions", purged),
+            Err(e) => log!(Level::Error, "Error deleting expired sessions: {}", crate::unroll_anyhow_result(e)),
+        }
+    }
+}
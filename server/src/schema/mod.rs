@@ -1,3 +1,11 @@
-pub mod blogpost;
+//! table definitions are generated from the migrations with `diesel print-schema`, do not edit generated.rs by hand
+mod generated;
 
-pub use blogpost::blogpost as BlogPostTable;
+pub use generated::*;
+pub use generated::blogpost as BlogPostTable;
+pub use generated::users as UserTable;
+pub use generated::session as SessionTable;
+pub use generated::comment as CommentTable;
+pub use generated::reaction as ReactionTable;
+pub use generated::tag as TagTable;
+pub use generated::blogpost_tag as BlogPostTagTable;
@@ -1,39 +1,408 @@
 use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::HashSet;
 use diesel::{
-    pg::PgConnection, ExpressionMethods, QueryDsl, RunQueryDsl
+    dsl::{exists, InnerJoin, IntoBoxed, Nullable, Select}, pg::{Pg, PgConnection}, sql_types::BigInt, BoolExpressionMethods, Connection,
+    ExpressionMethods, NullableExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SelectableHelper, TextExpressionMethods
 };
+use log::{log, Level};
 use crate::{
-    models::{BlogPost, CreateBlogPostDTO, NewPost},
-    schema::blogpost::blogpost::table as BlogpostTable,
+    auth::AuthenticatedUser,
+    service::image_service::{mark_image_deleted, restore_image},
+    models::{
+        BlogPost, CreateBlogPostDTO, FeedCursor, FeedItem, NewPost, PostChanges, UpdateBlogPostDTO, STATUS_PUBLISHED, STATUS_SCHEDULED
+    },
+    schema::{blogpost::table as BlogpostTable, users::table as UsersTable, BlogPostTagTable, TagTable, UserTable},
 };
 
-/// number of blogposts in a feed page
-const PAGE_SIZE: i64 = 5;
+/// position in the feed from which the blogposts are returned
+pub enum FeedPosition {
+    /// pages are numbered from 1
+    Page(u32),
+    /// blogposts that come after the cursor
+    After(FeedCursor),
+}
+
+/// restricts which blogposts are in the feed, blogposts have to match all of the provided filters
+pub struct FeedFilter {
+    /// normalized tag name
+    pub tag: Option<String>,
+    /// username of the author's account, legacy blogposts without an account never match
+    pub username: Option<String>,
+    /// one of POST_STATUSES, None only for the author's own blogposts
+    pub status: Option<String>,
+}
+
+/// only the published blogposts
+impl Default for FeedFilter {
+    fn default() -> Self {
+        FeedFilter {
+            tag: None,
+            username: None,
+            status: Some(STATUS_PUBLISHED.to_string()),
+        }
+    }
+}
+
+/// outcome of an operation that only the author of the blogpost is allowed to perform
+pub enum AuthorOnly<T> {
+    Done(T),
+    NotFound,
+    NotAuthor,
+}
+
+/// longest generated slug, leaves room for the number appended to the taken ones
+const MAX_SLUG_BASE_SIZE: usize = 90;
+/// advisory lock held while a slug is picked and saved
+///
+/// one key for all slugs, different titles can end up with the same slug,
+/// e.g. "my-title-2" is both the base of "My Title 2" and the second "My Title"
+const SLUG_LOCK_KEY: i64 = 0x736c7567;
+
+/// returns a slug made from the title that no other blogpost has, taken slugs get "-2", "-3", ... appended
+///
+/// has to be called inside the transaction that saves the slug, concurrent transactions
+/// that make a slug wait for it to finish
+fn free_slug(conn: &mut PgConnection, title: &str) -> Result<String> {
+    use crate::schema::BlogPostTable::dsl::{blogpost, slug};
+
+    let mut base = ::slug::slugify(title);
+    if base.len() > MAX_SLUG_BASE_SIZE {
+        // slugs are ascii
+        base.truncate(MAX_SLUG_BASE_SIZE);
+        base.truncate(base.trim_end_matches('-').len());
+    }
+    if base.is_empty() { base = "post".to_string(); }
 
+    diesel::sql_query("SELECT pg_advisory_xact_lock($1)")
+        .bind::<BigInt, _>(SLUG_LOCK_KEY)
+        .execute(conn)
+        .context("locking the slug")?;
+
+    // slugs only have letters, digits and '-', so they do not need escaping
+    let taken = blogpost
+        .filter(slug.eq(&base).or(slug.like(format!("{base}-%"))))
+        .select(slug)
+        .load::<Option<String>>(conn)
+        .context("getting taken slugs")?;
+    let taken: HashSet<String> = taken.into_iter().flatten().collect();
+
+    let free = std::iter::once(base.clone())
+        .chain((2..).map(|n| format!("{base}-{n}")))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("there are more candidates than taken slugs");
+    Ok(free)
+}
+
+/// saves the blogpost together with its tags, tags have to be normalized
+/// returns the saved blogpost
 pub fn create_blogpost(
     conn: &mut PgConnection,
     dto: CreateBlogPostDTO,
-    avatar: Option<String>,
-    image: Option<String>) -> Result<()> {
-    let post = NewPost::from_create_blog_post_dto(dto, avatar, image);
+    tags: Vec<String>,
+    author: &AuthenticatedUser,
+    image: Option<String>) -> Result<BlogPost> {
+    let mut post = NewPost::from_create_blog_post_dto(dto, author, image);
+
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        if let Some(title) = &post.title {
+            post.slug = Some(free_slug(conn, title)?);
+        }
+        let post_id = diesel::insert_into(BlogpostTable)
+            .values(&post)
+            .returning(crate::schema::BlogPostTable::id)
+            .get_result::<i32>(conn)?;
+        if !tags.is_empty() { save_tags(conn, post_id, &tags)?; }
+
+        get_blogpost(conn, post_id)?
+            .context("blogpost disappeared after saving it")
+    })
+    .context("saving blogpost")
+}
+
+/// adds the tags to the blogpost, tags that are used for the first time are created
+fn save_tags(conn: &mut PgConnection, post_id: i32, tags: &[String]) -> Result<()> {
+
+    let new_tags: Vec<_> = tags.iter().map(|name| TagTable::name.eq(name)).collect();
+    diesel::insert_into(TagTable::table)
+        .values(&new_tags)
+        .on_conflict(TagTable::name)
+        .do_nothing()
+        .execute(conn)
+        .context("saving tags")?;
+    let tag_ids = TagTable::table
+        .filter(TagTable::name.eq_any(tags))
+        .select(TagTable::id)
+        .load::<i32>(conn)
+        .context("getting tag ids")?;
 
-    diesel::insert_into(BlogpostTable)
-        .values(&post)
+    let post_tags: Vec<_> = tag_ids
+        .into_iter()
+        .map(|tag_id| (BlogPostTagTable::post_id.eq(post_id), BlogPostTagTable::tag_id.eq(tag_id)))
+        .collect();
+    diesel::insert_into(BlogPostTagTable::table)
+        .values(&post_tags)
         .execute(conn)
-        .map(|_| ())
+        .context("tagging blogpost")?;
+
+    Ok(())
+}
+
+/// returns the number of blogposts in the feed
+pub fn count_blogposts(conn: &mut PgConnection, filter: &FeedFilter) -> Result<i64> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    let mut query = blogpost
+        .count()
+        .into_boxed();
+    if let Some(tag) = &filter.tag {
+        query = query.filter(id.eq_any(tagged_with(tag)));
+    }
+    if let Some(author) = &filter.username {
+        query = query.filter(user_id.eq_any(user_with_username(author)));
+    }
+    if let Some(post_status) = &filter.status {
+        query = query.filter(status.eq(post_status));
+    }
+
+    query
+        .get_result::<i64>(conn)
+        .map_err(anyhow::Error::from)
+        .context("counting blogposts")
+}
+
+/// ids of the blogposts with the tag
+fn tagged_with(tag: &str) -> IntoBoxed<'_, Select<InnerJoin<BlogPostTagTable::table, TagTable::table>, BlogPostTagTable::post_id>, Pg> {
+    BlogPostTagTable::table
+        .inner_join(TagTable::table)
+        .select(BlogPostTagTable::post_id)
+        .into_boxed()
+        .filter(TagTable::name.eq(tag))
+}
+
+/// id of the user with the username
+///
+/// blogposts are matched by the id and not by their username column,
+/// so an account that takes the username of a legacy author does not get their blogposts
+fn user_with_username(name: &str) -> IntoBoxed<'_, Select<UserTable::table, Nullable<UserTable::id>>, Pg> {
+    UserTable::table
+        .select(UserTable::id.nullable())
+        .into_boxed()
+        .filter(UserTable::username.eq(name))
+}
+
+/// returns up to `limit` blogposts, ordered from the newest to the oldest blogpost,
+/// and the cursor pointing to the last returned blogpost if there are more blogposts after it
+///
+/// ties in the publication time are broken by the id, so the order is stable across pages
+pub fn get_blogposts(
+    conn: &mut PgConnection,
+    position: FeedPosition,
+    filter: &FeedFilter,
+    limit: u32) -> Result<(Vec<FeedItem>, Option<FeedCursor>)> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    let limit = limit as i64;
+    let mut query = blogpost
+        .left_join(UsersTable)
+        .select(FeedItem::as_select())
+        .order((published_at.desc(), id.desc()))
+        // one extra to know if there is a next page
+        .limit(limit + 1)
+        .into_boxed();
+
+    if let Some(tag) = &filter.tag {
+        query = query.filter(id.eq_any(tagged_with(tag)));
+    }
+    if let Some(author) = &filter.username {
+        query = query.filter(user_id.eq_any(user_with_username(author)));
+    }
+    if let Some(post_status) = &filter.status {
+        query = query.filter(status.eq(post_status));
+    }
+    query = match position {
+        FeedPosition::Page(page) => query.offset(((page as i64)-1) * limit),
+        FeedPosition::After(cursor) => query.filter(
+            published_at.lt(cursor.published_at)
+                .or(published_at.eq(cursor.published_at).and(id.lt(cursor.id)))),
+    };
+
+    let mut blogposts = query
+        .load::<FeedItem>(conn)
         .map_err(anyhow::Error::from)
-        .context("saving blogpost")
+        .context("getting blogposts")?;
+
+    let mut next_cursor = None;
+    if blogposts.len() as i64 > limit {
+        blogposts.truncate(limit as usize);
+        next_cursor = blogposts.last().map(|post| FeedCursor {
+            published_at: post.published_at,
+            id: post.id,
+        });
+    }
+
+    Ok((blogposts, next_cursor))
 }
 
-/// returns PAGE_SIZE number of results, ordered from the newest to the oldest blogpost
-pub fn get_blogposts(conn: &mut PgConnection, page: u32) -> Result<Vec<BlogPost>> {
+/// returns the blogposts with the given ids, ordered from the newest to the oldest like the feed
+pub fn get_blogposts_by_ids(conn: &mut PgConnection, post_ids: &[i32]) -> Result<Vec<BlogPost>> {
     use crate::schema::BlogPostTable::dsl::*;
 
     blogpost
-        .order(dateofpublication.desc())
-        .limit(PAGE_SIZE)
-        .offset(((page as i64)-1) * PAGE_SIZE)
+        .left_join(UsersTable)
+        .filter(id.eq_any(post_ids))
+        .order((published_at.desc(), id.desc()))
+        .select(BlogPost::as_select())
         .load::<BlogPost>(conn)
         .map_err(anyhow::Error::from)
-        .context("getting blogposts")
+        .context("getting blogposts by ids")
+}
+
+/// checks that the blogpost exists and the user can see it, see `BlogPost::is_visible_to`
+pub fn is_blogpost_visible(conn: &mut PgConnection, post_id: i32, viewer_id: Option<i32>) -> Result<bool> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    // user_id = NULL never matches, so only published blogposts are visible without a user
+    diesel::select(exists(
+        blogpost
+            .filter(id.eq(post_id))
+            .filter(status.eq(STATUS_PUBLISHED).or(user_id.eq(viewer_id)))))
+        .get_result::<bool>(conn)
+        .map_err(anyhow::Error::from)
+        .context(format!("checking if blogpost {post_id} is visible"))
+}
+
+/// returns the blogpost with the given id, Ok(None) if it does not exist
+pub fn get_blogpost(conn: &mut PgConnection, post_id: i32) -> Result<Option<BlogPost>> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    blogpost
+        .left_join(UsersTable)
+        .filter(id.eq(post_id))
+        .select(BlogPost::as_select())
+        .first::<BlogPost>(conn)
+        .optional()
+        .map_err(anyhow::Error::from)
+        .context(format!("getting blogpost {post_id}"))
+}
+
+/// returns the blogpost with the given slug, Ok(None) if it does not exist
+pub fn get_blogpost_by_slug(conn: &mut PgConnection, post_slug: &str) -> Result<Option<BlogPost>> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    blogpost
+        .left_join(UsersTable)
+        .filter(slug.eq(post_slug))
+        .select(BlogPost::as_select())
+        .first::<BlogPost>(conn)
+        .optional()
+        .map_err(anyhow::Error::from)
+        .context(format!("getting blogpost {post_slug}"))
+}
+
+/// updates the title, the text and/or the image of the blogpost and records the time of the edit
+/// blogposts without a slug get one when they get a title
+/// returns the updated blogpost and the uuid of the image that got replaced
+pub fn update_blogpost(
+    conn: &mut PgConnection,
+    post_id: i32,
+    author_id: i32,
+    dto: UpdateBlogPostDTO,
+    image: Option<String>) -> Result<AuthorOnly<(BlogPost, Option<String>)>> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        let current = blogpost
+            .find(post_id)
+            .select((postimage, user_id, slug, status))
+            .for_update()
+            .first::<(Option<String>, Option<i32>, Option<String>, String)>(conn)
+            .optional()?;
+        let Some((old_image, post_author, old_slug, old_status)) = current else { return Ok(AuthorOnly::NotFound) };
+        if post_author != Some(author_id) { return Ok(AuthorOnly::NotAuthor) }
+
+        let replaced_image = if image.is_some() { old_image } else { None };
+        let mut changes = PostChanges::from_update_blog_post_dto(dto, image);
+        if let (Some(Some(new_title)), None) = (&changes.title, old_slug) {
+            changes.slug = Some(free_slug(conn, new_title)?);
+        }
+        // blogposts appear in the feed at the time they get published
+        if changes.status.as_deref() == Some(STATUS_PUBLISHED) && old_status != STATUS_PUBLISHED {
+            changes.published_at = Some(Utc::now());
+        }
+        diesel::update(blogpost.find(post_id))
+            .set(&changes)
+            .execute(conn)?;
+        // the avatar comes from the author profile, so the post is selected again
+        let post = get_blogpost(conn, post_id)?
+            .context("blogpost disappeared during the update")?;
+
+        Ok(AuthorOnly::Done((post, replaced_image)))
+    })
+    .context(format!("updating blogpost {post_id}"))
+}
+
+/// publishes the scheduled blogposts whose publish_at has passed, they are published at their publish_at time
+/// returns the number of published blogposts
+pub fn publish_scheduled(conn: &mut PgConnection) -> Result<usize> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    diesel::update(blogpost)
+        .filter(status.eq(STATUS_SCHEDULED))
+        .filter(publish_at.le(Utc::now()))
+        .set((
+            status.eq(STATUS_PUBLISHED),
+            published_at.eq(publish_at.assume_not_null()),
+        ))
+        .execute(conn)
+        .context("publishing scheduled blogposts")
+}
+
+/// deletes the blogpost, returns the marked post image and avatar (see image_service::mark_image_deleted)
+/// that should be deleted once this function returns
+///
+/// files are marked inside the transaction and restored if it fails,
+/// so a row never points to a missing file
+pub fn delete_blogpost(
+    conn: &mut PgConnection,
+    post_id: i32,
+    author_id: i32) -> Result<AuthorOnly<(Option<String>, Option<String>)>> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    let mut marked: Vec<String> = Vec::new();
+    let res = conn.transaction::<_, anyhow::Error, _>(|conn| {
+        let post_author = blogpost
+            .find(post_id)
+            .select(user_id)
+            .for_update()
+            .first::<Option<i32>>(conn)
+            .optional()?;
+        let Some(post_author) = post_author else { return Ok(AuthorOnly::NotFound) };
+        if post_author != Some(author_id) { return Ok(AuthorOnly::NotAuthor) }
+
+        let (image_id, avatar_id) = diesel::delete(blogpost.find(post_id))
+            .returning((postimage, avatar))
+            .get_result::<(Option<String>, Option<String>)>(conn)?;
+
+        let mut mark = |image: Option<String>| -> Result<Option<String>> {
+            let Some(image) = image else { return Ok(None) };
+            let marked_id = mark_image_deleted(&image)?;
+            if let Some(marked_id) = &marked_id { marked.push(marked_id.clone()); }
+            Ok(marked_id)
+        };
+        let image_id = mark(image_id)?;
+        let avatar_id = mark(avatar_id)?;
+
+        Ok(AuthorOnly::Done((image_id, avatar_id)))
+    });
+
+    if res.is_err() {
+        for marked_id in marked {
+            if let Err(e) = restore_image(&marked_id) {
+                log!(Level::Error, "Error restoring an image: {}", crate::unroll_anyhow_result(e));
+            }
+        }
+    }
+
+    res.context(format!("deleting blogpost {post_id}"))
 }
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use diesel::{
+    dsl::exists, pg::PgConnection, Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    SelectableHelper
+};
+use crate::{
+    auth::AuthenticatedUser,
+    models::{Comment, CreateCommentDTO, NewComment},
+    schema::{CommentTable, UserTable},
+    service::blogpost_service::{is_blogpost_visible, AuthorOnly},
+};
+
+/// outcome of adding a comment to a blogpost
+pub enum NewCommentOutcome {
+    Created(Comment),
+    PostNotFound,
+    /// parent does not exist, belongs to another blogpost, or was deleted
+    ParentNotFound,
+    /// reply would be nested deeper than allowed
+    TooDeep,
+}
+
+/// adds a comment or a reply to the blogpost,
+/// drafts and scheduled blogposts can only be commented on by their author
+///
+/// replies can be nested up to `max_depth`, top level comments have depth 0
+pub fn create_comment(
+    conn: &mut PgConnection,
+    post_id: i32,
+    dto: CreateCommentDTO,
+    author: &AuthenticatedUser,
+    max_depth: i32) -> Result<NewCommentOutcome> {
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        if !is_blogpost_visible(conn, post_id, Some(author.id))? { return Ok(NewCommentOutcome::PostNotFound) }
+
+        let (root_id, depth) = match dto.parent_id {
+            Some(parent_id) => {
+                // locking the parent so it cannot be removed before the reply is saved
+                let parent = CommentTable::table
+                    .find(parent_id)
+                    .select((CommentTable::post_id, CommentTable::root_id, CommentTable::depth, CommentTable::deleted_at))
+                    .for_share()
+                    .first::<(i32, Option<i32>, i32, Option<DateTime<Utc>>)>(conn)
+                    .optional()?;
+                let Some((parent_post_id, parent_root_id, parent_depth, parent_deleted_at)) = parent else {
+                    return Ok(NewCommentOutcome::ParentNotFound)
+                };
+                if parent_post_id != post_id || parent_deleted_at.is_some() {
+                    return Ok(NewCommentOutcome::ParentNotFound)
+                }
+                if parent_depth >= max_depth { return Ok(NewCommentOutcome::TooDeep) }
+
+                (Some(parent_root_id.unwrap_or(parent_id)), parent_depth + 1)
+            }
+            None => (None, 0),
+        };
+
+        let comment = NewComment::from_create_comment_dto(dto, post_id, author, root_id, depth);
+        let comment_id = diesel::insert_into(CommentTable::table)
+            .values(&comment)
+            .returning(CommentTable::id)
+            .get_result::<i32>(conn)?;
+
+        let comment = CommentTable::table
+            .inner_join(UserTable::table)
+            .filter(CommentTable::id.eq(comment_id))
+            .select(Comment::as_select())
+            .first::<Comment>(conn)?;
+
+        Ok(NewCommentOutcome::Created(comment))
+    })
+    .context(format!("saving comment on blogpost {post_id}"))
+}
+
+/// returns the nth page of top level comments on the blogpost, ordered from the oldest to the newest,
+/// each followed by all of its replies, and the number of top level comments on the blogpost
+/// returns Ok(None) if the blogpost does not exist or the user cannot see it
+pub fn get_comments(
+    conn: &mut PgConnection,
+    post_id: i32,
+    viewer_id: Option<i32>,
+    page: u32,
+    limit: u32) -> Result<Option<(Vec<Comment>, i64)>> {
+    if !is_blogpost_visible(conn, post_id, viewer_id)? { return Ok(None) }
+
+    let limit = limit as i64;
+    let top_level = CommentTable::table
+        .inner_join(UserTable::table)
+        .filter(CommentTable::post_id.eq(post_id))
+        .filter(CommentTable::parent_id.is_null())
+        .order((CommentTable::created_at.asc(), CommentTable::id.asc()))
+        .limit(limit)
+        .offset(((page as i64)-1) * limit)
+        .select(Comment::as_select())
+        .load::<Comment>(conn)
+        .context(format!("getting comments on blogpost {post_id}"))?;
+
+    let root_ids: Vec<i32> = top_level.iter().map(|comment| comment.id).collect();
+    let replies = CommentTable::table
+        .inner_join(UserTable::table)
+        .filter(CommentTable::root_id.eq_any(root_ids))
+        .order((CommentTable::created_at.asc(), CommentTable::id.asc()))
+        .select(Comment::as_select())
+        .load::<Comment>(conn)
+        .context(format!("getting replies on blogpost {post_id}"))?;
+
+    let total_count = CommentTable::table
+        .filter(CommentTable::post_id.eq(post_id))
+        .filter(CommentTable::parent_id.is_null())
+        .count()
+        .get_result::<i64>(conn)
+        .context(format!("counting comments on blogpost {post_id}"))?;
+
+    Ok(Some((thread_order(top_level, replies), total_count)))
+}
+
+/// orders the comments so every reply comes right after the comment it replies to,
+/// replies to the same comment keep their relative order
+fn thread_order(top_level: Vec<Comment>, replies: Vec<Comment>) -> Vec<Comment> {
+    let mut children: HashMap<i32, Vec<Comment>> = HashMap::new();
+    for reply in replies {
+        // replies always have a parent
+        let Some(parent_id) = reply.parent_id else { continue };
+        children.entry(parent_id).or_default().push(reply);
+    }
+
+    let mut ordered = Vec::with_capacity(top_level.len());
+    let mut stack: Vec<Comment> = top_level.into_iter().rev().collect();
+    while let Some(comment) = stack.pop() {
+        if let Some(replies) = children.remove(&comment.id) {
+            stack.extend(replies.into_iter().rev());
+        }
+        ordered.push(comment);
+    }
+
+    ordered
+}
+
+/// deletes the comment, comments that have replies are turned into tombstones instead
+/// so the replies stay in place, tombstones left without replies are deleted as well
+pub fn delete_comment(conn: &mut PgConnection, post_id: i32, comment_id: i32, author_id: i32) -> Result<AuthorOnly<()>> {
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        let comment_author = CommentTable::table
+            .find(comment_id)
+            .filter(CommentTable::post_id.eq(post_id))
+            .filter(CommentTable::deleted_at.is_null())
+            .select((CommentTable::user_id, CommentTable::parent_id))
+            .for_update()
+            .first::<(i32, Option<i32>)>(conn)
+            .optional()?;
+        let Some((comment_author, mut parent_id)) = comment_author else { return Ok(AuthorOnly::NotFound) };
+        if comment_author != author_id { return Ok(AuthorOnly::NotAuthor) }
+
+        let has_replies = diesel::select(exists(CommentTable::table.filter(CommentTable::parent_id.eq(comment_id))))
+            .get_result::<bool>(conn)?;
+        if has_replies {
+            diesel::update(CommentTable::table.find(comment_id))
+                .set((CommentTable::text.eq(""), CommentTable::deleted_at.eq(Utc::now())))
+                .execute(conn)?;
+            return Ok(AuthorOnly::Done(()));
+        }
+
+        diesel::delete(CommentTable::table.find(comment_id))
+            .execute(conn)?;
+
+        // walking up the thread, removing the tombstones whose last reply is gone
+        while let Some(tombstone_id) = parent_id {
+            // locking the tombstone so replies deleted at the same time do not both see the other one still there
+            let tombstone = CommentTable::table
+                .find(tombstone_id)
+                .filter(CommentTable::deleted_at.is_not_null())
+                .select(CommentTable::parent_id)
+                .for_update()
+                .first::<Option<i32>>(conn)
+                .optional()?;
+            let Some(tombstone_parent_id) = tombstone else { break };
+
+            let has_replies = diesel::select(exists(CommentTable::table.filter(CommentTable::parent_id.eq(tombstone_id))))
+                .get_result::<bool>(conn)?;
+            if has_replies { break }
+
+            diesel::delete(CommentTable::table.find(tombstone_id))
+                .execute(conn)?;
+            parent_id = tombstone_parent_id;
+        }
+
+        Ok(AuthorOnly::Done(()))
+    })
+    .context(format!("deleting comment {comment_id} on blogpost {post_id}"))
+}
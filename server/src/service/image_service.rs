@@ -1,83 +1,506 @@
-use std::io::ErrorKind;
+use std::{io::{Cursor, ErrorKind, SeekFrom}, time::Duration};
 use anyhow::{anyhow, Context, Result};
 use futures_util::TryStreamExt;
+use image::codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::{WebPDecoder, WebPEncoder}};
+use image::{imageops::FilterType, metadata::Orientation, DynamicImage, ImageDecoder, ImageEncoder, ImageReader, Limits};
+use log::{log, Level};
 use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
 use tokio_util::io::ReaderStream;
 use uuid::Uuid;
-use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader, Take};
 use tokio::fs::{remove_file, File};
-use crate::models::MAX_IMAGE_SIZE;
+use crate::models::{ImageFormat, ImageMeta, ImageVariant, IMAGE_SNIFF_SIZE, IMAGE_VARIANTS, MAX_IMAGE_SIZE};
 
 const IMAGE_FILEPATH: &str = "./images";
-const PNG_MAGIC_BYTES: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
-
-/// saves image in the image folder
-/// function returns the image uuid, if image is larger than MAX_IMAGE_SIZE, and if image is PNG
-/// in cases when the image is too large or not PNG, function still returns Ok
-pub async fn save_image(image: &mut actix_multipart::Field) -> Result<(String, bool, bool)> {
-    let image_id = Uuid::new_v4();
-    let image_id = image_id.to_string();
+/// suffix of images that are waiting to be deleted
+const DELETED_SUFFIX: &str = "deleted";
+/// suffix of the file next to the image that holds its ImageMeta
+const META_SUFFIX: &str = "meta";
+/// shipped with the server and replaced between releases, so its ETag is never stored
+pub const PLACEHOLDER_AVATAR: &str = "placeholder_avatar";
+/// widest and tallest image that is accepted, larger images are not decoded
+const MAX_DECODED_SIZE: u32 = 8192;
+/// quality of the JPEG variants
+const JPEG_QUALITY: u8 = 80;
+/// quality of the uploaded JPEG images when they are encoded again without the metadata
+const ORIGINAL_JPEG_QUALITY: u8 = 90;
+/// RIFF chunks of WebP images that hold the metadata
+const WEBP_METADATA_CHUNKS: [&[u8; 4]; 2] = [b"EXIF", b"XMP "];
+/// VP8X chunk of WebP images declares in its first byte which chunks the image has
+const VP8X_CHUNK: &[u8; 4] = b"VP8X";
+const VP8X_METADATA_FLAGS: u8 = 0b0000_1100;
+
+/// what happened to the chunk written into an ImageFile
+enum ChunkOutcome {
+    Written,
+    TooLarge,
+    NotAllowed,
+}
+
+/// image that is being saved, the beginning of the image is held back until its format is recognized
+struct ImageFile {
+    image_id: String,
+    file: File,
+    head: Vec<u8>,
+    format: Option<ImageFormat>,
+    size: usize,
+}
+
+impl ImageFile {
+    async fn create() -> Result<Self> {
+        let image_id = Uuid::new_v4().to_string();
+        let file = File::create(format!("{IMAGE_FILEPATH}/{image_id}"))
+            .await
+            .context("creating an image file")?;
+
+        Ok(ImageFile {
+            image_id,
+            file,
+            head: Vec::with_capacity(IMAGE_SNIFF_SIZE),
+            format: None,
+            size: 0,
+        })
+    }
+
+    async fn write(&mut self, chunk: &[u8], allowed_formats: &[ImageFormat]) -> Result<ChunkOutcome> {
+        self.size += chunk.len();
+        if self.size > MAX_IMAGE_SIZE { return Ok(ChunkOutcome::TooLarge); }
+
+        if self.format.is_some() {
+            self.file.write_all(chunk).await.context("writing image data")?;
+            return Ok(ChunkOutcome::Written);
+        }
+
+        self.head.extend_from_slice(chunk);
+        if self.head.len() < IMAGE_SNIFF_SIZE { return Ok(ChunkOutcome::Written); }
+        self.recognize(allowed_formats).await
+    }
+
+    /// checks the format of the held back beginning and writes it
+    async fn recognize(&mut self, allowed_formats: &[ImageFormat]) -> Result<ChunkOutcome> {
+        let format = ImageFormat::sniff(&self.head).filter(|format| allowed_formats.contains(format));
+        if format.is_none() { return Ok(ChunkOutcome::NotAllowed); }
+        self.format = format;
+
+        self.file.write_all(&self.head).await.context("writing image data")?;
+        Ok(ChunkOutcome::Written)
+    }
+
+    /// writes the rest of a complete image, returns its format
+    /// Ok(None) if the image is shorter than IMAGE_SNIFF_SIZE and not in one of the allowed formats
+    async fn finish(mut self, allowed_formats: &[ImageFormat]) -> Result<Option<ImageFormat>> {
+        if self.format.is_none() {
+            if let ChunkOutcome::NotAllowed = self.recognize(allowed_formats).await? { return Ok(None); }
+        }
+
+        self.file.flush().await.context("writing image data")?;
+        Ok(self.format)
+    }
+
+    async fn remove(self) -> Result<()> {
+        drop(self.file);
+        remove_file(format!("{IMAGE_FILEPATH}/{}", self.image_id))
+            .await
+            .context(format!("deleting unfinished image: {}", self.image_id))
+    }
+}
+
+/// name of the file that holds the variant of the image
+pub fn variant_id(image_id: &str, variant: ImageVariant) -> String {
+    format!("{image_id}.{}", variant.name)
+}
+
+fn codec_format(format: ImageFormat) -> image::ImageFormat {
+    match format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Jpeg => image::ImageFormat::Jpeg,
+        ImageFormat::Gif => image::ImageFormat::Gif,
+        ImageFormat::Webp => image::ImageFormat::WebP,
+    }
+}
+
+/// decodes the first frame of the image, returns it with its EXIF orientation and ICC profile
+fn decode_image(content: &[u8], format: ImageFormat) -> image::ImageResult<(DynamicImage, Orientation, Option<Vec<u8>>)> {
+    let mut reader = ImageReader::with_format(Cursor::new(content), codec_format(format));
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODED_SIZE);
+    limits.max_image_height = Some(MAX_DECODED_SIZE);
+    reader.limits(limits);
+
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let icc_profile = decoder.icc_profile()?;
+    let image = DynamicImage::from_decoder(decoder)?;
+    Ok((image, orientation, icc_profile))
+}
+
+/// encodes the image keeping only its ICC profile, so the colors do not change
+fn encode_image(encoder: impl ImageEncoder, image: &DynamicImage, icc_profile: Option<Vec<u8>>) -> Result<()> {
+    let mut encoder = encoder;
+    if let Some(icc_profile) = icc_profile {
+        encoder.set_icc_profile(icc_profile).context("setting the ICC profile")?;
+    }
+    encoder.write_image(image.as_bytes(), image.width(), image.height(), image.color().into())
+        .context("encoding image")
+}
+
+/// copies the frames of a GIF image, comments and application extensions other than the loop count are left out
+fn strip_gif(content: &[u8]) -> Result<Vec<u8>> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(content).context("decoding GIF")?;
+    let global_palette = decoder.global_palette().unwrap_or_default().to_vec();
+
+    let mut stripped = Vec::with_capacity(content.len());
+    let mut encoder = gif::Encoder::new(&mut stripped, decoder.width(), decoder.height(), &global_palette)
+        .context("encoding GIF")?;
+    encoder.set_repeat(decoder.repeat()).context("encoding GIF")?;
+    while let Some(frame) = decoder.read_next_frame().context("decoding GIF")? {
+        encoder.write_frame(frame).context("encoding GIF")?;
+    }
+    drop(encoder);
+
+    Ok(stripped)
+}
+
+/// copies the RIFF chunks of a WebP image without the EXIF and XMP chunks, None if the container is malformed
+fn strip_webp(content: &[u8]) -> Option<Vec<u8>> {
+    let riff_size = u32::from_le_bytes(content.get(4..8)?.try_into().ok()?) as usize;
+    let mut chunks = content.get(IMAGE_SNIFF_SIZE..(riff_size + 8).min(content.len()))?;
+
+    let mut stripped = content[..IMAGE_SNIFF_SIZE].to_vec();
+    while !chunks.is_empty() {
+        let fourcc = chunks.get(..4)?;
+        let size = u32::from_le_bytes(chunks.get(4..8)?.try_into().ok()?) as usize;
+        // chunks are padded to an even size, the padding of the last one is sometimes missing
+        let chunk = chunks.get(..8 + size)?;
+        chunks = chunks.get(8 + size + size % 2..).unwrap_or_default();
+
+        if WEBP_METADATA_CHUNKS.iter().any(|metadata| fourcc == *metadata) { continue; }
+        let flags = stripped.len() + 8;
+        stripped.extend_from_slice(chunk);
+        if size % 2 == 1 { stripped.push(0); }
+        if fourcc == VP8X_CHUNK && size > 0 { stripped[flags] &= !VP8X_METADATA_FLAGS; }
+    }
+
+    let riff_size = u32::try_from(stripped.len() - 8).ok()?;
+    stripped[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(stripped)
+}
+
+/// removes the metadata from the uploaded image, returns the new content of the image and its upright first frame
+/// Ok(None) if the image can not be decoded
+///
+/// JPEG and PNG images are encoded again with the EXIF orientation applied to the pixels,
+/// GIF and WebP images keep their frames and only lose the metadata,
+/// still WebP images that are not upright are encoded again losslessly
+fn strip_metadata(content: &[u8], format: ImageFormat) -> Result<Option<(Vec<u8>, DynamicImage)>> {
+    let (mut image, orientation, icc_profile) = match decode_image(content, format) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            log!(Level::Warn, "Uploaded image can not be decoded: {}", e);
+            return Ok(None);
+        }
+    };
+    let animated = format == ImageFormat::Webp && WebPDecoder::new(Cursor::new(content)).is_ok_and(|decoder| decoder.has_animation());
+    if !animated { image.apply_orientation(orientation); }
+
+    let mut stripped = Vec::new();
+    match format {
+        ImageFormat::Jpeg => {
+            let image = DynamicImage::ImageRgb8(image.to_rgb8());
+            encode_image(JpegEncoder::new_with_quality(&mut stripped, ORIGINAL_JPEG_QUALITY), &image, icc_profile)?;
+        }
+        ImageFormat::Png => encode_image(PngEncoder::new(&mut stripped), &image, icc_profile)?,
+        ImageFormat::Gif => match strip_gif(content) {
+            Ok(gif) => stripped = gif,
+            Err(e) => {
+                log!(Level::Warn, "Uploaded GIF image can not be copied: {}", crate::unroll_anyhow_result(e));
+                return Ok(None);
+            }
+        },
+        ImageFormat::Webp if !animated && orientation != Orientation::NoTransforms => {
+            let image = DynamicImage::ImageRgba8(image.to_rgba8());
+            encode_image(WebPEncoder::new_lossless(&mut stripped), &image, icc_profile)?;
+        }
+        ImageFormat::Webp => match strip_webp(content) {
+            Some(webp) => stripped = webp,
+            None => return Ok(None),
+        },
+    }
+
+    Ok(Some((stripped, image)))
+}
+
+/// resizes the image into the variants that are narrower than it,
+/// variants of JPEG images are JPEG and the others are PNG, animated images get variants of their first frame
+///
+/// function is blocking
+fn generate_variants(image_id: &str, image: &DynamicImage, format: ImageFormat, variants: &[ImageVariant]) -> Result<()> {
+    for variant in variants {
+        if image.width() <= variant.width { continue; }
+        let resized = image.resize(variant.width, u32::MAX, FilterType::Lanczos3);
+
+        let mut encoded = Vec::new();
+        let format = if format == ImageFormat::Jpeg {
+            JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY)
+                .encode_image(&DynamicImage::ImageRgb8(resized.to_rgb8()))
+                .context(format!("encoding {} variant of image {image_id}", variant.name))?;
+            ImageFormat::Jpeg
+        } else {
+            resized.write_to(&mut Cursor::new(&mut encoded), image::ImageFormat::Png)
+                .context(format!("encoding {} variant of image {image_id}", variant.name))?;
+            ImageFormat::Png
+        };
+
+        let variant_id = variant_id(image_id, *variant);
+        std::fs::write(format!("{IMAGE_FILEPATH}/{variant_id}"), &encoded)
+            .context(format!("writing image {variant_id}"))?;
+        write_image_meta(&variant_id, &ImageMeta::new(format, format!("{:x}", Sha256::digest(&encoded))))?;
+    }
+
+    Ok(())
+}
+
+/// replaces the saved image with its copy without the metadata, then stores its ImageMeta and generates its variants
+/// returns false if the image can not be decoded
+///
+/// function is blocking
+fn process_image(image_id: &str, format: ImageFormat, variants: &[ImageVariant]) -> Result<bool> {
     let filepath = format!("{IMAGE_FILEPATH}/{image_id}");
+    let content = std::fs::read(&filepath).context(format!("reading image {image_id}"))?;
+    let stripped = strip_metadata(&content, format)
+        .context(format!("removing the metadata of image {image_id}"))?;
+    let Some((content, image)) = stripped else { return Ok(false) };
 
-    let filepath_clone = filepath.clone();
-    let mut file = File::create(&filepath_clone)
-        .await
-        .context("creating file")?;
+    std::fs::write(&filepath, &content).context(format!("writing image {image_id}"))?;
+    write_image_meta(image_id, &ImageMeta::new(format, format!("{:x}", Sha256::digest(&content))))?;
+    generate_variants(image_id, &image, format, variants)?;
+    Ok(true)
+}
 
-    let mut chunk_size = 0;
-    let mut chunk_too_large = false;
-    let mut checked_format = false;
+/// saves image in the image folder together with its variants, the metadata of the image is removed
+/// function returns the image uuid, if image is larger than MAX_IMAGE_SIZE, and if image is in one of the allowed formats
+/// in cases when the image is too large, not in an allowed format or can not be decoded,
+/// the image is deleted and function still returns Ok
+///
+/// the field is always read to the end, a completed field must not be read again
+pub async fn save_image(
+    image: &mut actix_multipart::Field,
+    allowed_formats: &[ImageFormat],
+    variants: &'static [ImageVariant]) -> Result<(String, bool, bool)> {
+    let file = ImageFile::create().await;
+    if file.is_err() { skip_field(image).await; }
+    let mut file = file?;
+    let image_id = file.image_id.clone();
 
     loop {
         let chunk = image.try_next().await;
-        if let Err(e) = chunk { return Err(anyhow!(e.to_string()).context("receiving image chunk")); }
+        if let Err(e) = chunk {
+            skip_field(image).await;
+            remove_unfinished(file).await;
+            return Err(anyhow!(e.to_string()).context("receiving image chunk"));
+        }
 
         let chunk = chunk.unwrap();
         if chunk.is_none() { break; }
         let chunk = chunk.unwrap();
 
-        chunk_size += chunk.len();
-        if chunk_size > MAX_IMAGE_SIZE {
-            chunk_too_large = true;
-            break;
+        let outcome = file.write(&chunk, allowed_formats).await;
+        if !matches!(outcome, Ok(ChunkOutcome::Written)) { skip_field(image).await; }
+        if let Err(e) = outcome {
+            remove_unfinished(file).await;
+            return Err(e);
         }
-
-        if !checked_format {
-            let first_eight_bytes: Vec<u8> = chunk.iter().take(8).map(|b| *b).collect();
-            if first_eight_bytes != PNG_MAGIC_BYTES {
-                return Ok((image_id, false, false))
+        match outcome.unwrap() {
+            ChunkOutcome::Written => {}
+            ChunkOutcome::TooLarge => {
+                file.remove().await?;
+                return Ok((image_id, true, true));
+            }
+            ChunkOutcome::NotAllowed => {
+                file.remove().await?;
+                return Ok((image_id, false, false));
             }
-            checked_format = true
         }
+    }
 
-        file.write_all(&(chunk))
-            .await
-            .context("writing image data")?;
+    let format = file.finish(allowed_formats).await;
+    if let Err(e) = format {
+        if let Err(remove_err) = delete_image(image_id).await {
+            log!(Level::Error, "Error removing a failed upload: {}", crate::unroll_anyhow_result(remove_err));
+        }
+        return Err(e);
     }
+    let is_allowed = match format.unwrap() {
+        Some(format) => process_saved_image(image_id.clone(), format, variants).await?,
+        None => {
+            delete_image(image_id.clone()).await?;
+            false
+        }
+    };
+    Ok((image_id, false, is_allowed))
+}
 
-    if chunk_too_large {
-        remove_file(filepath)
-            .await
-            .context(format!("deleting image that is too large: {image_id}"))?;
-        return Ok((image_id, true, true));
+/// removes the file of an upload that failed, the failure is only logged
+/// so the error that stopped the upload is the one returned
+async fn remove_unfinished(file: ImageFile) {
+    if let Err(e) = file.remove().await {
+        log!(Level::Error, "Error removing a failed upload: {}", crate::unroll_anyhow_result(e));
+    }
+}
+
+/// reads the rest of the field without keeping it
+async fn skip_field(field: &mut actix_multipart::Field) {
+    while let Ok(Some(_bytes)) = field.try_next().await {}
+}
+
+/// strips the metadata of the saved image and generates its variants, see process_image
+/// the image is deleted if that fails or the image can not be decoded
+async fn process_saved_image(image_id: String, format: ImageFormat, variants: &'static [ImageVariant]) -> Result<bool> {
+    let image_id_clone = image_id.clone();
+    let res = tokio::task::spawn_blocking(move || process_image(&image_id_clone, format, variants))
+        .await
+        .context("processing image")
+        .and_then(|res| res);
+    if !matches!(res, Ok(true)) { delete_image(image_id).await?; }
+    res
+}
+
+/// removes the file, Ok if it does not exist
+async fn remove_file_if_exists(filepath: String) -> Result<()> {
+    match remove_file(&filepath).await {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(anyhow!(e).context(format!("deleting {filepath}"))),
     }
+}
 
-    Ok((image_id, false, true))
+/// files that belong to the image besides the image itself: its ImageMeta, its variants and their ImageMeta
+fn companion_files(image_id: &str) -> Vec<String> {
+    let mut files = vec![format!("{image_id}.{META_SUFFIX}")];
+    for variant in IMAGE_VARIANTS {
+        let variant_id = variant_id(image_id, variant);
+        files.push(format!("{variant_id}.{META_SUFFIX}"));
+        files.push(variant_id);
+    }
+    files
 }
 
+/// deletes the image, its variants and their ImageMeta, image_id can also be the name returned by mark_image_deleted
 pub async fn delete_image(image_id: String) -> Result<()> {
     let filepath = format!("{IMAGE_FILEPATH}/{image_id}");
     remove_file(filepath)
         .await
         .context(format!("deleting image: {image_id}"))?;
 
+    // files of a marked image were marked together with it
+    let (image_id, suffix) = match image_id.strip_suffix(&format!(".{DELETED_SUFFIX}")) {
+        Some(image_id) => (image_id, format!(".{DELETED_SUFFIX}")),
+        None => (image_id.as_str(), String::new()),
+    };
+    for file in companion_files(image_id) {
+        remove_file_if_exists(format!("{IMAGE_FILEPATH}/{file}{suffix}")).await?;
+    }
+
     Ok(())
 }
 
-/// function returns the image uuid if the image was successfully downloaded and saved
-/// image uuid is None if the provided url is not an image, not a PNG,
-/// or larger then MAX_IMAGE_SIZE
-pub async fn download_avatar(image_url: &String) -> Result<Option<String>> {
+/// renames the file, Ok(false) if it does not exist
+fn rename_if_exists(from: &str, to: &str) -> Result<bool> {
+    match std::fs::rename(format!("{IMAGE_FILEPATH}/{from}"), format!("{IMAGE_FILEPATH}/{to}")) {
+        Ok(_) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(anyhow!(e).context(format!("renaming {from} to {to}"))),
+    }
+}
+
+/// marks the image for deletion by renaming it together with its variants and their ImageMeta,
+/// marked image is no longer served but can still be restored
+/// returns the name of the marked file that should be passed to delete_image, Ok(None) if the image does not exist
+///
+/// marked images that are left behind are removed by delete_marked_images
+///
+/// function is blocking, it is meant to be called inside a db transaction
+pub fn mark_image_deleted(image_id: &str) -> Result<Option<String>> {
+    let marked_id = format!("{image_id}.{DELETED_SUFFIX}");
+    let marked = rename_if_exists(image_id, &marked_id)
+        .context(format!("marking image for deletion: {image_id}"))?;
+    if !marked { return Ok(None) }
+
+    // the time of the marking, so leftovers can be told apart from images of a running deletion
+    let touched = std::fs::File::options()
+        .write(true)
+        .open(format!("{IMAGE_FILEPATH}/{marked_id}"))
+        .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+
+    let mut res = touched.map_err(anyhow::Error::from);
+    for file in companion_files(image_id) {
+        if res.is_err() { break; }
+        res = rename_if_exists(&file, &format!("{file}.{DELETED_SUFFIX}")).map(|_| ());
+    }
+    if let Err(e) = res {
+        if let Err(restore_err) = restore_image(&marked_id) {
+            log!(Level::Error, "Error restoring an image: {}", crate::unroll_anyhow_result(restore_err));
+        }
+        return Err(e.context(format!("marking image for deletion: {image_id}")));
+    }
+
+    Ok(Some(marked_id))
+}
+
+/// restores the image previously marked by mark_image_deleted together with its variants and their ImageMeta
+pub fn restore_image(marked_id: &str) -> Result<()> {
+    let image_id = marked_id
+        .strip_suffix(&format!(".{DELETED_SUFFIX}"))
+        .ok_or(anyhow!("image is not marked for deletion: {marked_id}"))?;
+
+    for file in companion_files(image_id) {
+        rename_if_exists(&format!("{file}.{DELETED_SUFFIX}"), &file)
+            .context(format!("restoring image: {image_id}"))?;
+    }
+    std::fs::rename(
+        format!("{IMAGE_FILEPATH}/{marked_id}"),
+        format!("{IMAGE_FILEPATH}/{image_id}"))
+        .context(format!("restoring image: {image_id}"))
+}
+
+/// deletes the images that were marked by mark_image_deleted longer than `age` ago,
+/// those are left behind when removing them after the deletion failed
+/// returns the number of deleted images
+pub async fn delete_marked_images(age: Duration) -> Result<usize> {
+    let mut entries = tokio::fs::read_dir(IMAGE_FILEPATH)
+        .await
+        .context("reading the images directory")?;
+
+    let mut deleted = 0;
+    while let Some(entry) = entries.next_entry().await.context("reading the images directory")? {
+        let Ok(name) = entry.file_name().into_string() else { continue };
+        // only the marked images, their other files go together with them
+        let Some(image_id) = name.strip_suffix(&format!(".{DELETED_SUFFIX}")) else { continue };
+        if Uuid::try_parse(image_id).is_err() { continue; }
+
+        let modified = entry.metadata()
+            .await
+            .and_then(|metadata| metadata.modified())
+            .context(format!("reading the marking time of image {image_id}"))?;
+        if modified.elapsed().unwrap_or_default() < age { continue; }
+
+        delete_image(name).await?;
+        deleted += 1;
+    }
+
+    Ok(deleted)
+}
+
+/// function returns the image uuid if the image was successfully downloaded and saved together with its variants
+/// image uuid is None if the provided url is not an image, not in one of the allowed formats,
+/// can not be decoded, or larger then MAX_IMAGE_SIZE
+pub async fn download_avatar(
+    image_url: &String,
+    allowed_formats: &[ImageFormat],
+    variants: &'static [ImageVariant]) -> Result<Option<String>> {
     let mut response = reqwest::get(image_url)
         .await
         .context(format!("downloading image from url {image_url}"))?;
@@ -87,48 +510,108 @@ pub async fn download_avatar(image_url: &String) -> Result<Option<String>> {
     let total_size = response.content_length().unwrap_or(0);
     if total_size > MAX_IMAGE_SIZE as u64 { return Ok(None); }
 
-    let image_id = Uuid::new_v4();
-    let image_id = image_id.to_string();
-    let filepath = format!("{IMAGE_FILEPATH}/{image_id}");
-    let mut file = File::create(&filepath)
-        .await
-        .context("creating an image file")?;
-
-    let mut downloaded_size = 0;
-    let mut checked_format = false;
+    let mut file = ImageFile::create().await?;
+    let image_id = file.image_id.clone();
 
-    while let Some(chunk) = response.chunk().await.context("reading image chunk")? {
-        downloaded_size += chunk.len();
-        if downloaded_size > MAX_IMAGE_SIZE { return Ok(None); }
+    loop {
+        let chunk = response.chunk().await.context("reading image chunk");
+        let chunk = match chunk {
+            Ok(Some(chunk)) => chunk,
+            Ok(None) => break,
+            Err(e) => {
+                file.remove().await?;
+                return Err(e);
+            }
+        };
 
-        if !checked_format {
-            let first_eight_bytes: Vec<u8> = chunk.iter().take(8).map(|b| *b).collect();
-            if first_eight_bytes != PNG_MAGIC_BYTES {
-                remove_file(&filepath)
+        let write_res = file.write(&chunk, allowed_formats).await;
+        match write_res {
+            Ok(ChunkOutcome::Written) => {}
+            Ok(ChunkOutcome::TooLarge | ChunkOutcome::NotAllowed) => {
+                file.remove().await?;
+                return Ok(None);
+            }
+            Err(e) => {
+                file.remove()
                     .await
-                    .context("deleting avatar because of wrong format")?;
-                return Ok(None)
+                    .context("deleting avatar because of an error encountered while writing")?;
+                return Err(e);
             }
-            checked_format = true
         }
+    }
 
-        let write_res = file.write_all(&chunk)
-            .await
-            .context("writing image chunk");
+    let format = file.finish(allowed_formats).await;
+    if let Err(e) = format {
+        if let Err(remove_err) = delete_image(image_id).await {
+            log!(Level::Error, "Error removing a failed upload: {}", crate::unroll_anyhow_result(remove_err));
+        }
+        return Err(e);
+    }
+    let is_allowed = match format.unwrap() {
+        Some(format) => process_saved_image(image_id.clone(), format, variants).await?,
+        None => {
+            delete_image(image_id.clone()).await?;
+            false
+        }
+    };
+    Ok(is_allowed.then_some(image_id))
+}
 
-        if write_res.is_err() {
-            remove_file(&filepath)
-                .await
-                .context("deleting avatar because of an error encountered while writing")?;
-            return Err(write_res.err().unwrap());
+/// writes the ImageMeta next to the image, the file is replaced at once so readers never see a partial one
+///
+/// function is blocking
+fn write_image_meta(image_id: &str, meta: &ImageMeta) -> Result<()> {
+    let meta_path = format!("{IMAGE_FILEPATH}/{image_id}.{META_SUFFIX}");
+    let tmp_path = format!("{meta_path}.{}", Uuid::new_v4());
+    std::fs::write(&tmp_path, serde_json::to_vec(meta)?)
+        .context(format!("writing the metadata of image {image_id}"))?;
+    std::fs::rename(&tmp_path, &meta_path)
+        .context(format!("writing the metadata of image {image_id}"))
+}
+
+/// MIME type and ETag of the stored image, Ok(None) if the image does not exist
+/// metadata of images saved before it was stored next to them is computed from the content and stored,
+/// the placeholder avatar is hashed on every call
+///
+/// function is blocking
+pub fn image_meta(image_id: &str) -> Result<Option<ImageMeta>> {
+    let is_placeholder = image_id == PLACEHOLDER_AVATAR;
+    if !is_placeholder {
+        match std::fs::read(format!("{IMAGE_FILEPATH}/{image_id}.{META_SUFFIX}")) {
+            Ok(meta) => return serde_json::from_slice(&meta)
+                .map(Some)
+                .context(format!("parsing the metadata of image {image_id}")),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(anyhow!(e).context(format!("reading the metadata of image {image_id}"))),
         }
     }
 
-    Ok(Some(image_id))
+    let image = match std::fs::read(format!("{IMAGE_FILEPATH}/{image_id}")) {
+        Ok(image) => image,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(anyhow!(e).context(format!("reading image {image_id}"))),
+    };
+    let format = ImageFormat::sniff(&image).ok_or(anyhow!("unknown format of image {image_id}"))?;
+    let meta = ImageMeta::new(format, format!("{:x}", Sha256::digest(&image)));
+    if !is_placeholder { write_image_meta(image_id, &meta)?; }
+
+    Ok(Some(meta))
+}
+
+/// size of the image in bytes, Ok(None) if the image does not exist
+///
+/// function is blocking
+pub fn image_size(image_id: &str) -> Result<Option<u64>> {
+    match std::fs::metadata(format!("{IMAGE_FILEPATH}/{image_id}")) {
+        Ok(metadata) => Ok(Some(metadata.len())),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(anyhow!(e).context(format!("reading image size: {image_id}"))),
+    }
 }
 
 /// read the image from the local storage, if the file does not exist function returns Ok(None)
-pub async fn get_image(image_id: String) -> Result<Option<ReaderStream<BufReader<File>>>> {
+/// `range` holds the first and the last byte when only a part of the image is read
+pub async fn get_image(image_id: String, range: Option<(u64, u64)>) -> Result<Option<ReaderStream<Take<BufReader<File>>>>> {
     let filepath = format!("{IMAGE_FILEPATH}/{image_id}");
 
     let file = tokio::fs::File::open(&filepath)
@@ -143,9 +626,19 @@ pub async fn get_image(image_id: String) -> Result<Option<ReaderStream<BufReader
             return Err(e);
         }
     }
-    let file = file.unwrap();
+    let mut file = file.unwrap();
+
+    let length = match range {
+        Some((start, end)) => {
+            file.seek(SeekFrom::Start(start))
+                .await
+                .context(format!("seeking image {image_id}"))?;
+            end - start + 1
+        }
+        None => u64::MAX,
+    };
 
-    let reader = BufReader::new(file);
+    let reader = BufReader::new(file).take(length);
     let stream = ReaderStream::new(reader);
 
     Ok(Some(stream))
@@ -1,2 +1,8 @@
 pub mod blogpost_service;
+pub mod comment_service;
 pub mod image_service;
+pub mod reaction_service;
+pub mod search_service;
+pub mod syndication_service;
+pub mod tag_service;
+pub mod user_service;
@@ -0,0 +1,52 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use diesel::{
+    pg::PgConnection, result::{DatabaseErrorKind, Error::DatabaseError},
+    ExpressionMethods, QueryDsl, RunQueryDsl
+};
+use crate::{models::NewReaction, schema::ReactionTable, service::blogpost_service::is_blogpost_visible};
+
+/// outcome of reacting to a blogpost
+pub enum NewReactionOutcome {
+    Added,
+    /// the user already reacted with the same kind
+    AlreadyReacted,
+    PostNotFound,
+}
+
+/// adds a reaction of the user to the blogpost, a user can react with every kind only once
+/// drafts and scheduled blogposts can only be reacted to by their author
+pub fn add_reaction(conn: &mut PgConnection, post_id: i32, user_id: i32, kind: &str) -> Result<NewReactionOutcome> {
+    if !is_blogpost_visible(conn, post_id, Some(user_id))? { return Ok(NewReactionOutcome::PostNotFound) }
+
+    let reaction = NewReaction {
+        post_id,
+        user_id,
+        kind: kind.to_string(),
+        created_at: Utc::now(),
+    };
+
+    let res = diesel::insert_into(ReactionTable::table)
+        .values(&reaction)
+        .execute(conn);
+
+    match res {
+        Ok(_) => Ok(NewReactionOutcome::Added),
+        Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Ok(NewReactionOutcome::AlreadyReacted),
+        Err(DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => Ok(NewReactionOutcome::PostNotFound),
+        Err(e) => Err(anyhow!(e).context(format!("saving reaction on blogpost {post_id}"))),
+    }
+}
+
+/// removes the reaction of the user from the blogpost, returns Ok(false) if there was no such reaction
+pub fn remove_reaction(conn: &mut PgConnection, post_id: i32, user_id: i32, kind: &str) -> Result<bool> {
+    let deleted = diesel::delete(
+        ReactionTable::table
+            .filter(ReactionTable::post_id.eq(post_id))
+            .filter(ReactionTable::user_id.eq(user_id))
+            .filter(ReactionTable::kind.eq(kind)))
+        .execute(conn)
+        .context(format!("deleting reaction on blogpost {post_id}"))?;
+
+    Ok(deleted > 0)
+}
@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use diesel::{
+    define_sql_function, dsl::sql, infix_operator, pg::{Pg, PgConnection},
+    query_builder::QueryId, sql_types::{SqlType, Text},
+    BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SelectableHelper
+};
+use crate::{
+    models::{FeedItem, SearchCursor, SearchResultDTO, STATUS_PUBLISHED},
+    schema::{blogpost::table as BlogpostTable, sql_types::Tsvector, users::table as UsersTable},
+};
+
+#[derive(QueryId, Clone, SqlType)]
+#[diesel(postgres_type(name = "tsquery", schema = "pg_catalog"))]
+pub struct Tsquery;
+
+#[derive(QueryId, Clone, SqlType)]
+#[diesel(postgres_type(name = "regconfig", schema = "pg_catalog"))]
+pub struct Regconfig;
+
+define_sql_function!(fn websearch_to_tsquery(config: Regconfig, query: Text) -> Tsquery);
+define_sql_function!(fn ts_rank(vector: Tsvector, query: Tsquery) -> Float);
+define_sql_function!(fn ts_headline(config: Regconfig, document: Text, query: Tsquery, options: Text) -> Text);
+infix_operator!(Matches, " @@ ", backend: Pg);
+
+/// marks the start and the end of a matched word in the headline,
+/// control characters are used so the text can be escaped before adding the html tags
+const MATCH_START: char = '\u{2}';
+const MATCH_END: char = '\u{3}';
+const HEADLINE_OPTIONS: &str = "StartSel=\u{2}, StopSel=\u{3}, MaxFragments=2, MaxWords=20, MinWords=5, FragmentDelimiter=\" ... \"";
+
+/// text search configuration the query is parsed with, matches the one of the text in the search vector
+fn english() -> diesel::expression::SqlLiteral<Regconfig> {
+    sql::<Regconfig>("'english'")
+}
+
+/// position in the search results from which the results are returned
+pub enum SearchPosition {
+    /// pages are numbered from 1
+    Page(u32),
+    /// results that come after the cursor
+    After(SearchCursor),
+}
+
+/// returns up to `limit` blogposts that match the query, ordered from the best to the worst match,
+/// and the cursor pointing to the last returned result if there are more results after it
+///
+/// the query is parsed with websearch_to_tsquery, so it supports quoted phrases, "or" and "-"
+pub fn search_blogposts(
+    conn: &mut PgConnection,
+    query: &str,
+    position: SearchPosition,
+    limit: u32) -> Result<(Vec<SearchResultDTO>, Option<SearchCursor>)> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    let tsquery = websearch_to_tsquery(english(), query.to_string());
+    let rank = ts_rank(search_vector, tsquery.clone());
+
+    let limit = limit as i64;
+    let mut db_query = BlogpostTable
+        .left_join(UsersTable)
+        .filter(Matches::new(search_vector, tsquery.clone()))
+        .filter(status.eq(STATUS_PUBLISHED))
+        .select((
+            FeedItem::as_select(),
+            rank.clone(),
+            ts_headline(english(), text, tsquery.clone(), HEADLINE_OPTIONS),
+        ))
+        .order((rank.clone().desc(), id.desc()))
+        // one extra to know if there is a next page
+        .limit(limit + 1)
+        .into_boxed();
+
+    db_query = match position {
+        SearchPosition::Page(page) => db_query.offset(((page as i64)-1) * limit),
+        SearchPosition::After(cursor) => db_query.filter(
+            rank.clone().lt(cursor.rank)
+                .or(rank.clone().eq(cursor.rank).and(id.lt(cursor.id)))),
+    };
+
+    let mut results = db_query
+        .load::<(FeedItem, f32, String)>(conn)
+        .context("searching blogposts")?;
+
+    let mut next_cursor = None;
+    if results.len() as i64 > limit {
+        results.truncate(limit as usize);
+        next_cursor = results.last().map(|(post, rank, _)| SearchCursor {
+            rank: *rank,
+            id: post.id,
+        });
+    }
+
+    let results = results
+        .into_iter()
+        .map(|(post, rank, headline)| SearchResultDTO {
+            blogpost: post,
+            rank,
+            snippet: highlight(&headline),
+        })
+        .collect();
+
+    Ok((results, next_cursor))
+}
+
+/// returns the number of blogposts that match the query
+pub fn count_search_results(conn: &mut PgConnection, query: &str) -> Result<i64> {
+    use crate::schema::BlogPostTable::dsl::*;
+
+    BlogpostTable
+        .filter(Matches::new(search_vector, websearch_to_tsquery(english(), query.to_string())))
+        .filter(status.eq(STATUS_PUBLISHED))
+        .count()
+        .get_result::<i64>(conn)
+        .context("counting search results")
+}
+
+/// escapes the headline and wraps the matched words in <mark></mark>
+fn highlight(headline: &str) -> String {
+    let mut snippet = String::with_capacity(headline.len());
+    for c in headline.chars() {
+        match c {
+            MATCH_START => snippet.push_str("<mark>"),
+            MATCH_END => snippet.push_str("</mark>"),
+            '&' => snippet.push_str("&amp;"),
+            '<' => snippet.push_str("&lt;"),
+            '>' => snippet.push_str("&gt;"),
+            '"' => snippet.push_str("&quot;"),
+            '\'' => snippet.push_str("&#39;"),
+            c => snippet.push(c),
+        }
+    }
+    snippet
+}
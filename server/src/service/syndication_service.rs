@@ -0,0 +1,214 @@
+use anyhow::{Context, Result};
+use atom_syndication as atom;
+use chrono::Utc;
+use diesel::pg::PgConnection;
+use crate::models::{BlogPost, JsonFeedAuthorDTO, JsonFeedDTO, JsonFeedItemDTO, JSON_FEED_VERSION};
+use crate::service::blogpost_service::{get_blogposts, get_blogposts_by_ids, FeedFilter, FeedPosition};
+use crate::service::image_service::{image_meta, image_size};
+
+/// number of the newest blogposts that are syndicated
+pub const SYNDICATION_SIZE: u32 = 20;
+const BLOG_TITLE: &str = "SimpleBlog";
+/// length of the generated atom entry titles, in characters
+const ENTRY_TITLE_SIZE: usize = 64;
+
+/// the newest blogposts that match the filter, ready to be written in one of the feed formats
+pub struct Syndication {
+    pub title: String,
+    /// address the server is reachable at, links to the blogposts and the images are built from it
+    pub public_url: String,
+    /// absolute url of the feed itself
+    pub self_url: String,
+    pub blogposts: Vec<BlogPost>,
+}
+
+/// reads the newest blogposts, the filter also decides the title of the feed
+pub fn get_syndication(
+    conn: &mut PgConnection,
+    filter: &FeedFilter,
+    public_url: &str,
+    self_url: String) -> Result<Syndication> {
+    // feed items do not carry the text, the syndicated blogposts are read in full
+    let (items, _) = get_blogposts(conn, FeedPosition::Page(1), filter, SYNDICATION_SIZE)
+        .context("getting syndicated blogposts")?;
+    let post_ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+    let blogposts = get_blogposts_by_ids(conn, &post_ids)
+        .context("getting syndicated blogposts")?;
+
+    let mut title = BLOG_TITLE.to_string();
+    if let Some(tag) = &filter.tag {
+        title.push_str(&format!(" #{tag}"));
+    }
+    if let Some(author) = &filter.username {
+        title.push_str(&format!(" by {author}"));
+    }
+
+    Ok(Syndication {
+        title,
+        public_url: public_url.to_string(),
+        self_url,
+        blogposts,
+    })
+}
+
+impl Syndication {
+    fn post_url(&self, post: &BlogPost) -> String {
+        format!("{}/api/v1/blogpost/{}", self.public_url, post.id)
+    }
+
+    fn image_url(&self, image_id: &str) -> String {
+        format!("{}/api/v1/image/{image_id}", self.public_url)
+    }
+
+    /// url, size and MIME type of the post image, None if the blogpost has no image or the file is missing
+    ///
+    /// function is blocking
+    fn enclosure(&self, post: &BlogPost) -> Result<Option<(String, u64, String)>> {
+        let Some(image_id) = &post.post_image else { return Ok(None) };
+        let (Some(size), Some(meta)) = (image_size(image_id)?, image_meta(image_id)?) else { return Ok(None) };
+        Ok(Some((self.image_url(image_id), size, meta.mime_type)))
+    }
+
+    /// RSS 2.0 document, authors are written as dc:creator because RSS expects an email in author
+    ///
+    /// function is blocking
+    pub fn to_rss(&self) -> Result<String> {
+        let mut items = Vec::with_capacity(self.blogposts.len());
+        for post in &self.blogposts {
+            let enclosure = self.enclosure(post)?.map(|(url, length, mime_type)| rss::Enclosure {
+                url,
+                length: length.to_string(),
+                mime_type,
+            });
+
+            items.push(rss::Item {
+                title: post.title.clone(),
+                link: Some(self.post_url(post)),
+                description: Some(post.html.0.clone()),
+                categories: post.tags.iter().map(|tag| rss::Category { name: tag.clone(), domain: None }).collect(),
+                enclosure,
+                guid: Some(rss::Guid { value: self.post_url(post), permalink: true }),
+                pub_date: Some(post.published_at.to_rfc2822()),
+                dublin_core_ext: Some(rss::extension::dublincore::DublinCoreExtension {
+                    creators: vec![post.username.clone()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        }
+
+        let channel = rss::Channel {
+            title: self.title.clone(),
+            link: self.public_url.clone(),
+            description: format!("The newest posts on {}", self.title),
+            last_build_date: Some(Utc::now().to_rfc2822()),
+            items,
+            ..Default::default()
+        };
+
+        Ok(channel.to_string())
+    }
+
+    /// Atom 1.0 document, entries without a title are titled with the beginning of the text
+    ///
+    /// function is blocking
+    pub fn to_atom(&self) -> Result<String> {
+        let mut entries = Vec::with_capacity(self.blogposts.len());
+        for post in &self.blogposts {
+            let mut links = vec![atom::Link {
+                href: self.post_url(post),
+                rel: "alternate".to_string(),
+                ..Default::default()
+            }];
+            if let Some((href, length, mime_type)) = self.enclosure(post)? {
+                links.push(atom::Link {
+                    href,
+                    rel: "enclosure".to_string(),
+                    mime_type: Some(mime_type),
+                    length: Some(length.to_string()),
+                    ..Default::default()
+                });
+            }
+
+            entries.push(atom::Entry {
+                title: atom::Text::plain(post.title.clone().unwrap_or_else(|| entry_title(&post.excerpt.0))),
+                id: self.post_url(post),
+                updated: post.edited_at.unwrap_or(post.published_at).fixed_offset(),
+                published: Some(post.published_at.fixed_offset()),
+                authors: vec![atom::Person { name: post.username.clone(), ..Default::default() }],
+                categories: post.tags.iter().map(|tag| atom::Category { term: tag.clone(), ..Default::default() }).collect(),
+                links,
+                summary: post.title.as_ref().map(|_| atom::Text::plain(post.excerpt.0.clone())),
+                content: Some(atom::Content {
+                    value: Some(post.html.0.clone()),
+                    content_type: Some("html".to_string()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        }
+
+        // the feed changes whenever one of its entries does
+        let updated = entries
+            .iter()
+            .map(|entry| entry.updated)
+            .max()
+            .unwrap_or_else(|| Utc::now().fixed_offset());
+
+        let feed = atom::Feed {
+            title: atom::Text::plain(self.title.clone()),
+            id: self.self_url.clone(),
+            updated,
+            links: vec![
+                atom::Link { href: self.self_url.clone(), rel: "self".to_string(), ..Default::default() },
+                atom::Link { href: self.public_url.clone(), rel: "alternate".to_string(), ..Default::default() },
+            ],
+            entries,
+            ..Default::default()
+        };
+
+        Ok(feed.to_string())
+    }
+
+    /// JSON Feed 1.1 document, the avatar of the author and the post image are linked by their urls
+    pub fn to_json_feed(&self) -> Result<String> {
+        let items = self.blogposts
+            .iter()
+            .map(|post| JsonFeedItemDTO {
+                id: post.id.to_string(),
+                url: Some(self.post_url(post)),
+                title: post.title.clone(),
+                summary: Some(post.excerpt.0.clone()),
+                content_html: post.html.0.clone(),
+                content_text: post.text.clone(),
+                image: post.post_image.as_deref().map(|image_id| self.image_url(image_id)),
+                date_published: post.published_at.to_rfc3339(),
+                date_modified: post.edited_at.map(|edited_at| edited_at.to_rfc3339()),
+                authors: vec![JsonFeedAuthorDTO {
+                    name: post.username.clone(),
+                    avatar: post.avatar.as_deref().map(|image_id| self.image_url(image_id)),
+                }],
+                tags: post.tags.clone(),
+            })
+            .collect();
+
+        let feed = JsonFeedDTO {
+            version: JSON_FEED_VERSION.to_string(),
+            title: self.title.clone(),
+            home_page_url: Some(self.public_url.clone()),
+            feed_url: Some(self.self_url.clone()),
+            items,
+        };
+
+        serde_json::to_string(&feed).context("serializing json feed")
+    }
+}
+
+/// excerpt shortened to ENTRY_TITLE_SIZE characters
+fn entry_title(excerpt: &str) -> String {
+    if excerpt.chars().count() <= ENTRY_TITLE_SIZE { return excerpt.to_string(); }
+
+    let mut title: String = excerpt.chars().take(ENTRY_TITLE_SIZE - 1).collect();
+    title.push('\u{2026}');
+    title
+}
@@ -0,0 +1,20 @@
+use anyhow::{Context, Result};
+use diesel::{dsl::count_star, pg::PgConnection, ExpressionMethods, QueryDsl, RunQueryDsl};
+use crate::{models::{TagDTO, STATUS_PUBLISHED}, schema::{BlogPostTable, BlogPostTagTable, TagTable}};
+
+/// returns the tags that are used by at least one published blogpost, the most used first
+pub fn get_tags(conn: &mut PgConnection) -> Result<Vec<TagDTO>> {
+    let tags = TagTable::table
+        .inner_join(BlogPostTagTable::table.inner_join(BlogPostTable::table))
+        .filter(BlogPostTable::status.eq(STATUS_PUBLISHED))
+        .group_by(TagTable::name)
+        .select((TagTable::name, count_star()))
+        .order((count_star().desc(), TagTable::name.asc()))
+        .load::<(String, i64)>(conn)
+        .context("getting tags")?;
+
+    Ok(tags
+        .into_iter()
+        .map(|(name, count)| TagDTO { name, count })
+        .collect())
+}
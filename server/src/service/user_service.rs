@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Context, Result};
+use argon2::{
+    password_hash::{rand_core::{OsRng, RngCore}, SaltString},
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{TimeDelta, Utc};
+use diesel::{
+    pg::PgConnection, result::{DatabaseErrorKind, Error::DatabaseError},
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl
+};
+use sha2::{Digest, Sha256};
+use crate::{
+    models::{CredentialsDTO, NewSession, NewUser, ProfileChanges, SessionDTO, UpdateProfileDTO, User},
+    schema::{SessionTable, UserTable},
+};
+
+/// how long a session stays valid after login
+const SESSION_DURATION: TimeDelta = TimeDelta::days(7);
+/// number of random bytes in a session token
+const TOKEN_SIZE: usize = 32;
+
+/// sessions are stored by the hash of their token, so leaked rows cannot be used to authenticate
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// creates a new user with a salted argon2 hash of the password
+/// returns Ok(None) if the username is already taken
+///
+/// hashing is cpu heavy, call from a blocking context
+pub fn create_user(conn: &mut PgConnection, dto: CredentialsDTO) -> Result<Option<User>> {
+    let salt = SaltString::generate(&mut OsRng);
+    let password_hash = Argon2::default()
+        .hash_password(dto.password.as_bytes(), &salt)
+        .map_err(|e| anyhow!(e.to_string()))
+        .context("hashing password")?
+        .to_string();
+
+    let user = NewUser {
+        username: dto.username,
+        password_hash,
+    };
+
+    let res = diesel::insert_into(UserTable::table)
+        .values(&user)
+        .get_result::<User>(conn);
+
+    match res {
+        Ok(user) => Ok(Some(user)),
+        Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Ok(None),
+        Err(e) => Err(anyhow!(e).context("saving user")),
+    }
+}
+
+/// checks the credentials and starts a new session
+/// returns Ok(None) if the username does not exist or the password is wrong
+///
+/// hashing is cpu heavy, call from a blocking context
+pub fn login(conn: &mut PgConnection, dto: CredentialsDTO) -> Result<Option<SessionDTO>> {
+    let user = UserTable::table
+        .filter(UserTable::username.eq(&dto.username))
+        .first::<User>(conn)
+        .optional()
+        .context(format!("getting user {}", dto.username))?;
+    let Some(user) = user else { return Ok(None) };
+
+    let password_hash = PasswordHash::new(&user.password_hash)
+        .map_err(|e| anyhow!(e.to_string()))
+        .context(format!("parsing password hash of user {}", user.id))?;
+    if Argon2::default().verify_password(dto.password.as_bytes(), &password_hash).is_err() {
+        return Ok(None);
+    }
+
+    let mut token = [0u8; TOKEN_SIZE];
+    OsRng.fill_bytes(&mut token);
+    let token = URL_SAFE_NO_PAD.encode(token);
+
+    let session = NewSession {
+        token_hash: hash_token(&token),
+        user_id: user.id,
+        expires_at: Utc::now() + SESSION_DURATION,
+    };
+    diesel::insert_into(SessionTable::table)
+        .values(&session)
+        .execute(conn)
+        .context("saving session")?;
+
+    Ok(Some(SessionDTO {
+        token,
+        expires_at: session.expires_at,
+    }))
+}
+
+/// returns the user the token belongs to, Ok(None) if the session does not exist or has expired
+pub fn get_session_user(conn: &mut PgConnection, token: &str) -> Result<Option<User>> {
+    SessionTable::table
+        .inner_join(UserTable::table)
+        .filter(SessionTable::token_hash.eq(hash_token(token)))
+        .filter(SessionTable::expires_at.gt(Utc::now()))
+        .select(UserTable::all_columns)
+        .first::<User>(conn)
+        .optional()
+        .map_err(anyhow::Error::from)
+        .context("getting session user")
+}
+
+/// ends the session, does nothing if the session does not exist
+pub fn logout(conn: &mut PgConnection, token: &str) -> Result<()> {
+    diesel::delete(SessionTable::table.find(hash_token(token)))
+        .execute(conn)
+        .map(|_| ())
+        .map_err(anyhow::Error::from)
+        .context("deleting session")
+}
+
+/// deletes the sessions whose expiry has passed, returns how many were deleted
+pub fn delete_expired_sessions(conn: &mut PgConnection) -> Result<usize> {
+    diesel::delete(SessionTable::table.filter(SessionTable::expires_at.le(Utc::now())))
+        .execute(conn)
+        .map_err(anyhow::Error::from)
+        .context("deleting expired sessions")
+}
+
+/// returns the user with the given username, Ok(None) if it does not exist
+pub fn get_user_by_username(conn: &mut PgConnection, username: &str) -> Result<Option<User>> {
+    UserTable::table
+        .filter(UserTable::username.eq(username))
+        .first::<User>(conn)
+        .optional()
+        .map_err(anyhow::Error::from)
+        .context(format!("getting user {username}"))
+}
+
+/// replaces the display name and the bio of the user
+pub fn update_profile(conn: &mut PgConnection, user_id: i32, dto: UpdateProfileDTO) -> Result<User> {
+    diesel::update(UserTable::table.find(user_id))
+        .set(&ProfileChanges::from_update_profile_dto(dto))
+        .get_result::<User>(conn)
+        .map_err(anyhow::Error::from)
+        .context(format!("updating profile of user {user_id}"))
+}
+
+/// sets the avatar of the user, returns the updated user and the uuid of the avatar that got replaced
+pub fn set_avatar(conn: &mut PgConnection, user_id: i32, avatar: String) -> Result<(User, Option<String>)> {
+    conn.transaction::<_, anyhow::Error, _>(|conn| {
+        let old_avatar = UserTable::table
+            .find(user_id)
+            .select(UserTable::avatar)
+            .for_update()
+            .first::<Option<String>>(conn)?;
+
+        let user = diesel::update(UserTable::table.find(user_id))
+            .set(UserTable::avatar.eq(avatar))
+            .get_result::<User>(conn)?;
+
+        Ok((user, old_avatar))
+    })
+    .context(format!("setting avatar of user {user_id}"))
+}